@@ -31,5 +31,8 @@ mod speedy2d;
 #[cfg(feature="speedy2d")]
 pub use speedy2d::*;
 
+#[cfg(not(target_arch="wasm32"))]
+mod watch;
+
 #[cfg(feature="dyn")]
 pub mod reload;
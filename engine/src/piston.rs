@@ -15,22 +15,40 @@
  */
 
 use interface::game::*;
+use interface::text::{MeasureText, TextMetrics};
 use piston_window::EventLoop;
 
 extern crate opengl_graphics;
-use self::opengl_graphics::{OpenGL, GlGraphics};
+use self::opengl_graphics::{OpenGL, GlGraphics, GlyphCache, Texture as GlTexture, TextureSettings};
+use self::opengl_graphics::{CreateTexture, Format};
 
 extern crate piston_window;
 use self::piston_window::{Event,Loop,RenderArgs,UpdateArgs,Input}; // from piston_input
 use self::piston_window::{ButtonArgs,ButtonState,Button,Motion}; // from piston_input
+use self::piston_window::{TouchArgs,Touch}; // from piston_input
 use self::piston_window::Key as pwKey; // from piston_input
 use self::piston_window::MouseButton as pwMouseButton; // from piston_input
 use self::piston_window::{Context,Transformed,color}; // from piston2d-graphics
 use self::piston_window::draw_state::Blend; // from piston2d-graphics
+use self::piston_window::Graphics as _; // from piston2d-graphics
+use self::piston_window::character::CharacterCache; // from piston2d-graphics
+use self::piston_window::draw_state::DrawState; // from piston2d-graphics
+use self::piston_window::triangulation::{tx, ty}; // from piston2d-graphics
 use self::piston_window::PistonWindow;
 use self::piston_window::WindowSettings; // from piston::window
 use self::piston_window::Events; // from piston::event_loop
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::watch::{watch, SETTLE};
+
+extern crate image;
+
 fn map_key(key: pwKey) -> Option<Key> {
     match key {
         pwKey::Up => Some(Key::ArrowUp),
@@ -40,10 +58,48 @@ fn map_key(key: pwKey) -> Option<Key> {
         pwKey::Escape => Some(Key::Escape),
         pwKey::Return => Some(Key::Enter),
         pwKey::Space => Some(Key::Space),
+        pwKey::Tab => Some(Key::Tab),
+        pwKey::Backspace => Some(Key::Backspace),
+        pwKey::F3 => Some(Key::F3),
+        pwKey::A => Some(Key::Letter('A')),
+        pwKey::B => Some(Key::Letter('B')),
+        pwKey::C => Some(Key::Letter('C')),
+        pwKey::D => Some(Key::Letter('D')),
+        pwKey::E => Some(Key::Letter('E')),
+        pwKey::F => Some(Key::Letter('F')),
+        pwKey::G => Some(Key::Letter('G')),
+        pwKey::H => Some(Key::Letter('H')),
+        pwKey::I => Some(Key::Letter('I')),
+        pwKey::J => Some(Key::Letter('J')),
+        pwKey::K => Some(Key::Letter('K')),
+        pwKey::L => Some(Key::Letter('L')),
+        pwKey::M => Some(Key::Letter('M')),
+        pwKey::N => Some(Key::Letter('N')),
+        pwKey::O => Some(Key::Letter('O')),
+        pwKey::P => Some(Key::Letter('P')),
+        pwKey::Q => Some(Key::Letter('Q')),
+        pwKey::R => Some(Key::Letter('R')),
+        pwKey::S => Some(Key::Letter('S')),
+        pwKey::T => Some(Key::Letter('T')),
+        pwKey::U => Some(Key::Letter('U')),
+        pwKey::V => Some(Key::Letter('V')),
+        pwKey::W => Some(Key::Letter('W')),
+        pwKey::X => Some(Key::Letter('X')),
+        pwKey::Y => Some(Key::Letter('Y')),
+        pwKey::Z => Some(Key::Letter('Z')),
         _ => None
     }
 }
 
+fn map_touch(touch: Touch) -> TouchPhase {
+    match touch {
+        Touch::Start => TouchPhase::Start,
+        Touch::Move => TouchPhase::Move,
+        Touch::End => TouchPhase::End,
+        Touch::Cancel => TouchPhase::Cancel,
+    }
+}
+
 fn map_button(b: pwMouseButton) -> Option<MouseButton> {
     match b {
         pwMouseButton::Left => Some(MouseButton::Left),
@@ -53,6 +109,132 @@ fn map_button(b: pwMouseButton) -> Option<MouseButton> {
     }
 }
 
+/// Decode a texture, printing why if it fails.
+fn load_texture(texture: Texture) -> Option<GlTexture> {
+    let image = image::load_from_memory(texture.data)
+        .map_err(|e| eprintln!("Cannot load texture {}: {}", texture.name, e))
+        .ok()?;
+    let image = image.to_rgba8();
+    // opengl_graphics uses a different version of image, so pass the pixels
+    GlTexture::create(&mut (), Format::Rgba8, &image, image.dimensions(), &TextureSettings::new())
+        .map_err(|e| eprintln!("Cannot load texture {}: {}", texture.name, e))
+        .ok()
+}
+
+/// Draw triangles with per-vertex colors, which piston only supports untransformed.
+fn draw_triangles(vertices: &[([f32; 2], Color)],  context: &Context,  g: &mut GlGraphics) {
+    let transform = context.transform;
+    let positions = vertices.iter()
+        .map(|&([x, y], _)| [tx(transform, x as f64, y as f64), ty(transform, x as f64, y as f64)])
+        .collect::<Vec<_>>();
+    let colors = vertices.iter().map(|&(_, color)| color.to_array()).collect::<Vec<_>>();
+    let triangles = positions.len() / 3 * 3;
+    g.tri_list_c(&context.draw_state, |f| f(&positions[..triangles], &colors[..triangles]));
+}
+
+/// The ascent and line height of the font relative to the font size,
+/// which speedy2d positions text by:
+/// the size is from the descent to the ascent, and the line gap is added between lines.
+fn font_metrics(glyphs: &GlyphCache) -> (f64, f64) {
+    let metrics = glyphs.font.v_metrics_unscaled();
+    let height = (metrics.ascent - metrics.descent) as f64;
+    (metrics.ascent as f64 / height,  1.0 + metrics.line_gap as f64 / height)
+}
+
+/// GlyphCache takes sizes in points, and draws them this many times as large in pixels.
+const PIXELS_PER_POINT: f64 = 1.333;
+
+/// The size to give GlyphCache for text that should be `pixels` high like in speedy2d.
+fn font_size(pixels: f64) -> u32 {
+    (pixels / PIXELS_PER_POINT).round() as u32
+}
+
+/// Lets `Graphics` measure text with the glyphs it's drawn with.
+struct GlyphMetrics(Rc<RefCell<GlyphCache<'static>>>);
+
+impl GlyphMetrics {
+    const LAYOUT_SIZE: u32 = 75;
+}
+
+impl MeasureText for GlyphMetrics {
+    fn measure(&self,  text: &str) -> TextMetrics {
+        let mut glyphs = self.0.borrow_mut();
+        let pixels = Self::LAYOUT_SIZE as f64 * PIXELS_PER_POINT;
+        let width = glyphs.width(Self::LAYOUT_SIZE, text).unwrap_or(0.0) / pixels;
+        let (ascent, _) = font_metrics(&glyphs);
+        // a single line is as high as the size, like in speedy2d
+        TextMetrics { width: width as f32,  height: 1.0,  baseline: ascent as f32 }
+    }
+}
+
+/// Split text into lines at newlines and between words,
+/// so that no line is wider than `max_width` unless a single word is.
+fn wrap_text<'a>(text: &'a str,  max_width: f64,  size: u32,  glyphs: &mut GlyphCache)
+-> Vec<(&'a str, f64)> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut start = 0;
+        let mut end = 0; // of the last word that fits
+        let mut width = 0.0;
+        for (i, _) in paragraph.match_indices(' ').chain(Some((paragraph.len(), ""))) {
+            let candidate_width = glyphs.width(size, &paragraph[start..i]).unwrap_or(0.0);
+            if candidate_width > max_width  &&  end > start {
+                lines.push((&paragraph[start..end], width));
+                start = end + 1; // skip the space
+                width = glyphs.width(size, &paragraph[start..i]).unwrap_or(0.0);
+            } else {
+                width = candidate_width;
+            }
+            end = i;
+        }
+        lines.push((&paragraph[start..], width));
+    }
+    lines
+}
+
+/// Draw lines of text the same way as the speedy2d backend:
+/// `box_width` is what the lines are aligned within horizontally,
+/// and the first line's ascent decides where the baseline is.
+#[allow(clippy::too_many_arguments)]
+fn draw_text_lines(
+        lines: &[(&str, f64)],  box_width: f64,
+        color: Color,  position: [f32; 2],  (align, valign): (Align, VAlign),  size: f32,  line_spacing: f32,
+        scale: f64,  glyphs: &mut GlyphCache,  draw_state: &DrawState,  context: &Context,  g: &mut GlGraphics,
+) {
+    // draw in pixels, so that glyphs are rendered at the size they're shown
+    let size = size as f64 * scale;
+    let transform = context.transform
+        .trans(position[0] as f64, position[1] as f64)
+        .zoom(1.0/scale);
+    let (ascent, line_height) = font_metrics(glyphs);
+    let line_height = size * line_height * line_spacing as f64;
+    let height = line_height * lines.len() as f64;
+    let top = match valign {
+        VAlign::Top => 0.0,
+        VAlign::Center => -height/2.0,
+        VAlign::Bottom => -height,
+        VAlign::Baseline => -size*ascent,
+    };
+    let left = match align {
+        Align::Left => 0.0,
+        Align::Center => -box_width/2.0,
+        Align::Right => -box_width,
+    };
+    let text = piston_window::Text::new_color(color.to_array(), font_size(size));
+    for (i, &(line, width)) in lines.iter().enumerate() {
+        let x = left + match align {
+            Align::Left => 0.0,
+            Align::Center => (box_width-width)/2.0,
+            Align::Right => box_width-width,
+        };
+        let baseline = top + line_height*i as f64 + size*ascent;
+        let transform = transform.trans(x.round(), baseline.round());
+        if let Err(e) = text.draw(line, glyphs, draw_state, transform, g) {
+            eprintln!("Cannot draw {:?}: {:?}", line, e);
+        }
+    }
+}
+
 #[inline(never)]
 pub fn start<G:Game>(mut game: G,  name: &'static str,  initial_size: [f32; 2]) {
     let window_size = [initial_size[0] as u32, initial_size[1] as u32];
@@ -63,9 +245,31 @@ pub fn start<G:Game>(mut game: G,  name: &'static str,  initial_size: [f32; 2])
     let mut g = GlGraphics::new(OpenGL::V3_2);
 
     let mut shapes = Graphics::default();
+    // GlyphCache only takes one font, so use the full one instead of the subset
+    // to not be missing any characters.
+    let glyphs = GlyphCache::from_bytes(
+            include_bytes!("../../font/FiraSans-Regular.ttf"),
+            (),
+            TextureSettings::new(),
+    ).expect("Parsing font");
+    // shared with the `GlyphMetrics` of `shapes`
+    let glyphs = Rc::new(RefCell::new(glyphs));
+    shapes.set_text_metrics(Box::new(GlyphMetrics(glyphs.clone())));
+    // by name, and None if decoding failed
+    let mut textures = HashMap::<String, Option<GlTexture>>::new();
     // changes if window is resized
     let mut size = [initial_size[0] as f64, initial_size[1] as f64];
     let mut offset = [0.0, 0.0];
+    game.resize([size[0] as f32, size[1] as f32]);
+
+    // piston has no way to wake up the event loop, so the changes are checked for on update
+    let (changed_tx, changed_rx) = mpsc::channel();
+    for &path in game.watched_files() {
+        let changed_tx = changed_tx.clone();
+        thread::spawn(move || {
+            watch(Path::new(path), SETTLE, &mut|| changed_tx.send(path).unwrap());
+        });
+    }
 
     let mut event_loop: Events = window.events;
     event_loop.set_ups(125); // default USB polling rate
@@ -81,14 +285,18 @@ pub fn start<G:Game>(mut game: G,  name: &'static str,  initial_size: [f32; 2])
                     let context: Context = context;
                     let g: &mut GlGraphics = g; // the same instance as outside
 
-                    size = context.get_view_size();
+                    let new_size = context.get_view_size();
+                    if new_size != size {
+                        size = new_size;
+                        game.resize([size[0] as f32, size[1] as f32]);
+                    }
                     let scale = f64::min(size[0], size[1]);
                     offset = [
                         (size[0]-scale) / 2.0,
                         (size[1]-scale) / 2.0,
                     ];
                     // Handle resized windows by scaling without stretching,
-                    // and adding letterboxing to center.
+                    // and centering the largest square.
                     let context = context.trans(offset[0], offset[1])
                                          .scale(scale, scale);
 
@@ -103,29 +311,150 @@ pub fn start<G:Game>(mut game: G,  name: &'static str,  initial_size: [f32; 2])
                     piston_window::clear(color::BLACK, g);
 
                     game.render(&mut shapes);
+                    let mut glyphs = glyphs.borrow_mut();
 
                     fn area_to_f64(area: [f32; 4]) -> [f64; 4] {
                         [area[0] as f64, area[1] as f64, area[2] as f64, area[3] as f64]
                     }
                     let transform = context.transform;
-                    for shape in shapes.drain() {
+                    for (clip, shape) in shapes.drain() {
+                        let draw_state = match clip {
+                            Some([x, y, w, h]) => {
+                                let left = (offset[0] + x as f64*scale).round().max(0.0);
+                                let top = (offset[1] + y as f64*scale).round().max(0.0);
+                                let right = (offset[0] + (x+w) as f64*scale).round().max(left);
+                                let bottom = (offset[1] + (y+h) as f64*scale).round().max(top);
+                                let area = [left, top, right-left, bottom-top].map(|v| v as u32);
+                                context.draw_state.scissor(area)
+                            }
+                            None => context.draw_state,
+                        };
+                        let context = Context { draw_state, ..context };
                         match shape {
                             Shape::Line { color, width, area } => {
                                 let area = area_to_f64(area);
-                                piston_window::line(color, width as f64, area, transform, g);
+                                piston_window::Line::new(color.to_array(), width as f64)
+                                    .draw(area, &draw_state, transform, g);
                             }
                             Shape::Rectangle { color, area } => {
-                                piston_window::rectangle(color, area_to_f64(area), transform, g);
+                                piston_window::Rectangle::new(color.to_array())
+                                    .draw(area_to_f64(area), &draw_state, transform, g);
                             }
                             Shape::Circle { color, center, radius } => {
                                 let area = area_to_f64([
                                     center[0]-radius, center[1]-radius,
                                     radius*2.0, radius*2.0,
                                 ]);
-                                piston_window::ellipse(color, area, transform, g)
+                                piston_window::Ellipse::new(color.to_array()).draw(area, &draw_state, transform, g);
+                            }
+                            // piston draws polygons as triangle fans
+                            Shape::Polygon { color, points } | Shape::TriangleFan { color, points } => {
+                                let points = points.iter()
+                                    .map(|&[x, y]| [x as f64, y as f64])
+                                    .collect::<Vec<_>>();
+                                piston_window::Polygon::new(color.to_array()).draw(&points, &draw_state, transform, g);
+                            }
+                            Shape::Ellipse { color, area } => {
+                                piston_window::Ellipse::new(color.to_array())
+                                    .draw(area_to_f64(area), &draw_state, transform, g);
+                            }
+                            Shape::RoundedRectangle { color, area, radius } => {
+                                let radius = radius.min(area[2]/2.0).min(area[3]/2.0);
+                                piston_window::Rectangle::new_round(color.to_array(), radius as f64)
+                                    .draw(area_to_f64(area), &draw_state, transform, g);
+                            }
+                            // circle_arc() centers the border on the circle
+                            Shape::Arc { color, width, center, radius, angles } => {
+                                let middle = radius - width/2.0;
+                                let area = area_to_f64([
+                                    center[0]-middle, center[1]-middle,
+                                    middle*2.0, middle*2.0,
+                                ]);
+                                let [start, end] = [angles[0] as f64, angles[1] as f64];
+                                piston_window::CircleArc::new(color.to_array(), width as f64 / 2.0, start, end)
+                                    .draw(area, &draw_state, transform, g);
+                            }
+                            Shape::Stroke { color, width, outline } => {
+                                for quad in outline.stroke(width) {
+                                    let quad = quad.map(|[x, y]| [x as f64, y as f64]);
+                                    piston_window::Polygon::new(color.to_array()).draw(&quad, &draw_state, transform, g);
+                                }
+                            }
+                            Shape::Image { tint, sprite, area } => {
+                                let texture = textures.entry(sprite.texture.name.to_string())
+                                    .or_insert_with(|| load_texture(sprite.texture));
+                                if let Some(texture) = texture {
+                                    let mut image = piston_window::Image::new_color(tint.to_array())
+                                        .rect(area_to_f64(area));
+                                    if let Some(source) = sprite.source {
+                                        image = image.src_rect(area_to_f64(source));
+                                    }
+                                    image.draw(texture, &draw_state, transform, g);
+                                }
+                            }
+                            // piston only draws images as transformed rectangles,
+                            // so the corner opposite of the first is ignored
+                            Shape::ImageQuad { tint, sprite, corners } => {
+                                let texture = textures.entry(sprite.texture.name.to_string())
+                                    .or_insert_with(|| load_texture(sprite.texture));
+                                if let Some(texture) = texture {
+                                    let [o, x, _, y] = corners.map(|[x, y]| [x as f64, y as f64]);
+                                    let unit_to_quad = [
+                                        [x[0]-o[0], y[0]-o[0], o[0]],
+                                        [x[1]-o[1], y[1]-o[1], o[1]],
+                                    ];
+                                    let mut image = piston_window::Image::new_color(tint.to_array())
+                                        .rect([0.0, 0.0, 1.0, 1.0]);
+                                    if let Some(source) = sprite.source {
+                                        image = image.src_rect(area_to_f64(source));
+                                    }
+                                    let transform = piston_window::math::multiply(transform, unit_to_quad);
+                                    image.draw(texture, &draw_state, transform, g);
+                                }
+                            }
+                            Shape::Gradient { gradient, points } => {
+                                draw_triangles(&gradient.triangles(&points), &context, g);
+                            }
+                            Shape::Triangles { vertices } => {
+                                draw_triangles(&vertices, &context, g);
+                            }
+                            Shape::StaticText { color, position, center, size, text } => {
+                                let size_px = font_size(size as f64 * scale);
+                                let width = glyphs.width(size_px, text).unwrap_or(0.0);
+                                draw_text_lines(
+                                        &[(text, width)], width,
+                                        color, position, center, size, 1.0,
+                                        scale, &mut glyphs, &draw_state, &context, g,
+                                );
                             }
-                            Shape::StaticText { .. } | Shape::DynamicText { .. } => {
-                                /*unimplemented*/
+                            Shape::DynamicText { color, position, center, size, text } => {
+                                let size_px = font_size(size as f64 * scale);
+                                let width = glyphs.width(size_px, &text).unwrap_or(0.0);
+                                draw_text_lines(
+                                        &[(text.as_str(), width)], width,
+                                        color, position, center, size, 1.0,
+                                        scale, &mut glyphs, &draw_state, &context, g,
+                                );
+                            }
+                            Shape::StaticTextBox { color, position, layout, size, text } => {
+                                let size_px = font_size(size as f64 * scale);
+                                let max_width = layout.max_width as f64 * scale;
+                                let lines = wrap_text(text, max_width, size_px, &mut glyphs);
+                                draw_text_lines(
+                                        &lines, max_width,
+                                        color, position, (layout.align, layout.valign), size, layout.line_spacing,
+                                        scale, &mut glyphs, &draw_state, &context, g,
+                                );
+                            }
+                            Shape::DynamicTextBox { color, position, layout, size, text } => {
+                                let size_px = font_size(size as f64 * scale);
+                                let max_width = layout.max_width as f64 * scale;
+                                let lines = wrap_text(&text, max_width, size_px, &mut glyphs);
+                                draw_text_lines(
+                                        &lines, max_width,
+                                        color, position, (layout.align, layout.valign), size, layout.line_spacing,
+                                        scale, &mut glyphs, &draw_state, &context, g,
+                                );
                             }
                         }
                     }
@@ -133,6 +462,9 @@ pub fn start<G:Game>(mut game: G,  name: &'static str,  initial_size: [f32; 2])
             }
             Event::Loop(Loop::Update(update_args)) => {
                 let UpdateArgs{dt: deltatime} = update_args;
+                for path in changed_rx.try_iter() {
+                    game.file_changed(path);
+                }
                 game.update(deltatime as f32);
             }
 
@@ -164,12 +496,30 @@ pub fn start<G:Game>(mut game: G,  name: &'static str,  initial_size: [f32; 2])
                     game.mouse_press(button);
                 }
             }
+            Event::Input(Input::Button(ButtonArgs {
+                    state: ButtonState::Release,
+                    button: Button::Mouse(button),
+                    ..
+            }), _) => {
+                if let Some(button) = map_button(button) {
+                    game.mouse_release(button);
+                }
+            }
             Event::Input(Input::Move(Motion::MouseCursor([x,y])), _) => {
                 let scale = f64::min(size[0], size[1]);
                 let x = (x-offset[0]) / scale;
                 let y = (y-offset[1]) / scale;
                 game.mouse_move([x as f32, y as f32]);
             }
+            Event::Input(Input::Move(Motion::Touch(touch_args)), _) => {
+                let touch_args: TouchArgs = touch_args;
+                let [x, y] = touch_args.position();
+                let scale = f64::min(size[0], size[1]);
+                let x = (x-offset[0]) / scale;
+                let y = (y-offset[1]) / scale;
+                let phase = map_touch(touch_args.touch);
+                game.touch(touch_args.id as u64, phase, [x as f32, y as f32]);
+            }
             // TODO pause when window loses focus (!= mouse leaves)
 
             _ => {}
@@ -17,7 +17,6 @@
 use interface::reloading::*;
 
 extern crate dlopen;
-extern crate notify;
 
 use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
 use std::fs;
@@ -25,12 +24,11 @@ use std::io::ErrorKind::*;
 use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
 use std::process::Command;
 use std::sync::atomic::{AtomicUsize, Ordering::*};
-use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
 use dlopen::raw::Library;
-use notify::{recommended_watcher, Watcher, Error, RecursiveMode};
-use notify::event::{Event, EventKind};
+
+use crate::watch::{watch, Delay};
 
 fn build_command(game_source_dir: &str,  cargo_args: &[&str]) -> Command {
     let mut cargo = Command::new("cargo");
@@ -105,54 +103,9 @@ fn reload(lib: &str,  current_size: usize) -> Option<&'static Functions> {
     }
 }
 
-
-/// Watch for changes in the folder with the game logic,
-/// and call a function at most once per second
-fn watch(src: &str,  callback: &mut dyn FnMut()) {
-    const DEBOUNCE_INTERVAL: Duration = Duration::from_secs(1);
-    let mut last_forwarded = Instant::now();
-    // run reloads on another thread than the one that handles events,
-    // so that the time between events is't affected by the reload time.
-    let (tx, rx) = mpsc::channel();
-    let debouncer = move |event: Result<Event,Error>| {
-        match event {
-            Ok(Event { kind: EventKind::Access(_), .. }) => {},
-            // Ok(Event { kind: EventKind::Modify(Modify::Metatdata), .. }) => {},
-            Ok(ev) => {
-                eprintln!("fs event: {:?}", ev);
-                let now = Instant::now();
-                if now.saturating_duration_since(last_forwarded) >= DEBOUNCE_INTERVAL {
-                    last_forwarded = now;
-                    tx.send(()).unwrap();
-                }
-            },
-            Err(e) => {
-                eprintln!("fs watch error: {} ({:?})", e, e.paths);
-            },
-        }
-    };
-    let mut watcher = match recommended_watcher(debouncer) {
-        Ok(watcher) => watcher,
-        Err(e) => {
-            eprintln!("Cannot create fs watcher: {} - Hotswapping will not work", e);
-            return;
-        }
-    };
-    if let Err(e) = watcher.watch(Path::new(src), RecursiveMode::NonRecursive) {
-        eprintln!("Cannot watch {:?}: {} - Hotswapping will not work", src, e);
-        return;
-    }
-
-    loop {
-        match rx.recv() {
-            Ok(()) => callback(),
-            Err(e) => {
-                eprintln!("fs watcher channel receive error: {}, quitting", e);
-                return;
-            }
-        }
-    }
-}
+/// Rebuild when the source changes, but at most once per second,
+/// as the build is started right away.
+const RELOAD_DELAY: Delay = Delay { settle: Duration::ZERO,  at_most_every: Duration::from_secs(1) };
 
 pub fn start_reloading(reloadable: &ReloadableGame) {
     let game_dir = reloadable.game_dir;
@@ -165,7 +118,7 @@ pub fn start_reloading(reloadable: &ReloadableGame) {
         println!("command: {:?}", &command);
         // for module mode to work, the source code cannot be inside a subdir.
         println!("Watching {:?} for source code changes", game_dir);
-        watch(game_dir, &mut|| {
+        watch(Path::new(game_dir), RELOAD_DELAY, &mut|| {
             let started = Instant::now();
             match command.status() {// runs the command
                 Ok(exit) if exit.success() => {},
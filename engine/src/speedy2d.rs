@@ -15,20 +15,28 @@
  */
 
 use interface::game::*;
+use interface::text::{MeasureText, TextMetrics};
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::io::Cursor;
 use std::rc::Rc;
 #[cfg(not(target_arch = "wasm32"))]
 use std::thread;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 
 extern crate speedy2d;
 use speedy2d::Graphics2D;
 use speedy2d::color::Color as spColor;
 use speedy2d::dimen::Vector2;
-use speedy2d::font::{Font, TextLayout, TextOptions, FormattedTextBlock};
-use speedy2d::shape::Rectangle;
+use speedy2d::image::{ImageHandle, ImageSmoothingMode};
+use speedy2d::font::{Font, FontFamily, TextAlignment, TextLayout, TextOptions, FormattedTextBlock};
+use speedy2d::shape::{Polygon, Rectangle, RoundedRectangle};
 use speedy2d::time::Stopwatch;
 use speedy2d::window::{
     MouseButton as spMouseButton,
@@ -38,6 +46,16 @@ use speedy2d::window::{
 };
 #[cfg(target_arch="wasm32")]
 use speedy2d::WebCanvas;
+#[cfg(target_arch="wasm32")]
+use speedy2d::window::UserEventSender;
+#[cfg(target_arch="wasm32")]
+extern crate wasm_bindgen;
+#[cfg(target_arch="wasm32")]
+use wasm_bindgen::{JsCast, closure::Closure};
+#[cfg(target_arch="wasm32")]
+extern crate web_sys;
+#[cfg(target_arch="wasm32")]
+extern crate log;
 #[cfg(not(target_arch = "wasm32"))]
 use speedy2d::Window;
 #[cfg(not(target_arch="wasm32"))]
@@ -51,10 +69,31 @@ use image::{ImageFormat, GenericImageView};
 extern crate fxhash;
 use fxhash::FxBuildHasher;
 
+#[cfg(not(target_arch="wasm32"))]
+use crate::watch::{watch, SETTLE};
+
 #[cfg(not(target_arch="wasm32"))]
 const UPDATE_RATE: u32 = 125; // the standard USB polling rate.
 #[cfg(not(target_arch="wasm32"))]
 const ICON: &[u8] = include_bytes!("../../wasm/favicon.ico");
+#[cfg(target_arch="wasm32")]
+const CANVAS_ID: &str = "space_tennis_game";
+/// For the text cache statistics shown with the debug layer.
+const DEBUG_TEXT_COLOR: Color = Color::rgba(1.0, 0.0, 1.0, 0.8);
+
+/// Events sent to the window handler from outside of speedy2d.
+#[derive(Clone,Copy, Debug)]
+enum EngineEvent {
+    /// Time to run `Game.update()`.
+    #[cfg(not(target_arch="wasm32"))]
+    Update,
+    /// One of `Game.watched_files()` has changed.
+    #[cfg(not(target_arch="wasm32"))]
+    FileChanged(&'static str),
+    /// speedy2d doesn't forward touch events, so they are listened for separately.
+    #[cfg(target_arch="wasm32")]
+    Touch { id: u64,  phase: TouchPhase,  pos: Vector2<f32> },
+}
 
 fn map_key(key: VirtualKeyCode) -> Option<Key> {
     match key {
@@ -65,6 +104,35 @@ fn map_key(key: VirtualKeyCode) -> Option<Key> {
         VirtualKeyCode::Escape => Some(Key::Escape),
         VirtualKeyCode::Return => Some(Key::Enter),
         VirtualKeyCode::Space => Some(Key::Space),
+        VirtualKeyCode::Tab => Some(Key::Tab),
+        VirtualKeyCode::Backspace => Some(Key::Backspace),
+        VirtualKeyCode::F3 => Some(Key::F3),
+        VirtualKeyCode::A => Some(Key::Letter('A')),
+        VirtualKeyCode::B => Some(Key::Letter('B')),
+        VirtualKeyCode::C => Some(Key::Letter('C')),
+        VirtualKeyCode::D => Some(Key::Letter('D')),
+        VirtualKeyCode::E => Some(Key::Letter('E')),
+        VirtualKeyCode::F => Some(Key::Letter('F')),
+        VirtualKeyCode::G => Some(Key::Letter('G')),
+        VirtualKeyCode::H => Some(Key::Letter('H')),
+        VirtualKeyCode::I => Some(Key::Letter('I')),
+        VirtualKeyCode::J => Some(Key::Letter('J')),
+        VirtualKeyCode::K => Some(Key::Letter('K')),
+        VirtualKeyCode::L => Some(Key::Letter('L')),
+        VirtualKeyCode::M => Some(Key::Letter('M')),
+        VirtualKeyCode::N => Some(Key::Letter('N')),
+        VirtualKeyCode::O => Some(Key::Letter('O')),
+        VirtualKeyCode::P => Some(Key::Letter('P')),
+        VirtualKeyCode::Q => Some(Key::Letter('Q')),
+        VirtualKeyCode::R => Some(Key::Letter('R')),
+        VirtualKeyCode::S => Some(Key::Letter('S')),
+        VirtualKeyCode::T => Some(Key::Letter('T')),
+        VirtualKeyCode::U => Some(Key::Letter('U')),
+        VirtualKeyCode::V => Some(Key::Letter('V')),
+        VirtualKeyCode::W => Some(Key::Letter('W')),
+        VirtualKeyCode::X => Some(Key::Letter('X')),
+        VirtualKeyCode::Y => Some(Key::Letter('Y')),
+        VirtualKeyCode::Z => Some(Key::Letter('Z')),
         _ => None
     }
 }
@@ -78,11 +146,12 @@ fn map_button(b: spMouseButton) -> Option<MouseButton> {
     }
 }
 
-fn map_color([r, g, b, a]: [f32; 4]) -> spColor {
+fn map_color(Color { r, g, b, a }: Color) -> spColor {
     spColor::from_rgba(r, g, b, a)
 }
 
-/// Creates square display area.
+/// Finds the largest square in the center of the window,
+/// which the game's coordinates are relative to.
 fn letterbox_and_scale(window_size: [f32; 2]) -> (Vector2<f32>, f32) {
     let scale = f32::min(window_size[0], window_size[1]);
     let offset = Vector2 {
@@ -92,26 +161,299 @@ fn letterbox_and_scale(window_size: [f32; 2]) -> (Vector2<f32>, f32) {
     (offset, scale)
 }
 
+/// Register listeners for touch events on the canvas,
+/// and forward them as user events with canvas-relative physical pixel positions.
+#[cfg(target_arch="wasm32")]
+fn listen_for_touch(sender: UserEventSender<EngineEvent>) {
+    let canvas = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(CANVAS_ID))
+        .expect("find canvas");
+    let events = [
+        ("touchstart", TouchPhase::Start),
+        ("touchmove", TouchPhase::Move),
+        ("touchend", TouchPhase::End),
+        ("touchcancel", TouchPhase::Cancel),
+    ];
+    for (event_name, phase) in events {
+        let sender = sender.clone();
+        let target = canvas.clone();
+        let listener = Closure::<dyn FnMut(web_sys::TouchEvent)>::new(move |event: web_sys::TouchEvent| {
+            // prevent scrolling and emulated mouse events
+            event.prevent_default();
+            let rect = target.get_bounding_client_rect();
+            let dpr = web_sys::window().map_or(1.0, |window| window.device_pixel_ratio());
+            let touches = event.changed_touches();
+            for touch in (0..touches.length()).filter_map(|i| touches.get(i)) {
+                let pos = Vector2 {
+                    x: ((touch.client_x() as f64 - rect.left()) * dpr) as f32,
+                    y: ((touch.client_y() as f64 - rect.top()) * dpr) as f32,
+                };
+                let id = touch.identifier() as u32 as u64;
+                if let Err(e) = sender.send_event(EngineEvent::Touch { id, phase, pos }) {
+                    log::warn!("forwarding {} failed: {}", event_name, e);
+                }
+            }
+        });
+        canvas.add_event_listener_with_callback(event_name, listener.as_ref().unchecked_ref())
+            .expect("listen for touch events");
+        // the listeners should live as long as the page
+        listener.forget();
+    }
+}
+
+fn draw_triangles(g: &mut Graphics2D,  vertices: &[([f32; 2], Color)],  offset: Vector2<f32>,  scale: f32) {
+    for triangle in vertices.chunks_exact(3) {
+        let points = [0, 1, 2].map(|i| {
+            let [x, y] = triangle[i].0;
+            Vector2 { x, y }*scale + offset
+        });
+        let colors = [0, 1, 2].map(|i| map_color(triangle[i].1));
+        g.draw_triangle_three_color(points, colors);
+    }
+}
+
+/// Measures text by laying it out at a fixed size,
+/// so that the results don't depend on the window size.
+/// The layouts are cached along with the ones that are drawn.
+struct FontMetrics(Rc<RefCell<TextCache>>);
+
+impl FontMetrics {
+    const LAYOUT_SIZE: f32 = 100.0;
+}
+
+impl MeasureText for FontMetrics {
+    fn measure(&self,  text: &str) -> TextMetrics {
+        let block = self.0.borrow_mut().get(Cow::Owned(text.to_owned()), Self::LAYOUT_SIZE, None);
+        let baseline = block.iter_lines().next().map_or(0.0, |line| line.ascent());
+        TextMetrics {
+            width: block.width() / Self::LAYOUT_SIZE,
+            height: block.height() / Self::LAYOUT_SIZE,
+            baseline: baseline / Self::LAYOUT_SIZE,
+        }
+    }
+}
+
+/// Where to draw `text` to have it aligned to `position` as requested.
+///
+/// `width` is the box it's aligned within horizontally,
+/// which for wrapped text is the maximum width.
+fn align_text(position: Vector2<f32>,  text: &FormattedTextBlock,  width: f32,  (align, valign): (Align, VAlign))
+-> Vector2<f32> {
+    let x = match align {
+        Align::Left => position.x,
+        Align::Center => position.x - width/2.0,
+        Align::Right => position.x - width,
+    };
+    let y = match valign {
+        VAlign::Top => position.y,
+        VAlign::Center => position.y - text.height()/2.0,
+        VAlign::Bottom => position.y - text.height(),
+        VAlign::Baseline => position.y - text.iter_lines().next().map_or(0.0, |line| line.ascent()),
+    };
+    Vector2 { x, y }
+}
+
+fn text_box_options(max_width: f32,  layout: TextBox) -> TextOptions {
+    let alignment = match layout.align {
+        Align::Left => TextAlignment::Left,
+        Align::Center => TextAlignment::Center,
+        Align::Right => TextAlignment::Right,
+    };
+    TextOptions::new()
+        .with_wrap_to_width(max_width, alignment)
+        .with_line_spacing_multiplier(layout.line_spacing)
+}
+
+/// What a text layout depends on.
+#[derive(Clone, PartialEq,Eq, Hash)]
+struct LayoutKey {
+    text: Cow<'static, str>,
+    /// The bits of the size in pixels, as the layout is made with the exact size.
+    size: u32,
+    /// The bits of the max width in pixels and the line spacing, and the alignment, for text boxes.
+    wrap: Option<(u32, u32, i8)>,
+}
+
+#[derive(Clone,Copy, Default, Debug)]
+struct CacheStats {
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl Display for CacheStats {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        let lookups = (self.hits + self.misses).max(1);
+        write!(fmtr, "{} hits ({:.1}%), {} misses, {} evicted",
+            self.hits, self.hits as f64 * 100.0 / lookups as f64, self.misses, self.evictions
+        )
+    }
+}
+
+/// The font the subset was made from, for characters the subset lacks.
+/// Left out of wasm builds, where download size matters more.
+#[cfg(not(target_arch="wasm32"))]
+const FULL_FONT: Option<&[u8]> = Some(include_bytes!("../../font/FiraSans-Regular.ttf"));
+#[cfg(target_arch="wasm32")]
+const FULL_FONT: Option<&[u8]> = None;
+
+/// Text layouts, both static and dynamic,
+/// where the least recently used ones are removed when there are too many.
 struct TextCache {
-    font: Font,
-    statics: HashMap<(&'static str, i32), Rc<FormattedTextBlock>, FxBuildHasher>,
+    /// The subset, and then the full font once a character was not in the subset.
+    fonts: FontFamily,
+    subset: Font,
+    /// Parsed when first needed.
+    full: Option<Font>,
+    /// Characters that `font/create_subset.sh` should include.
+    /// Each is printed the first time it's laid out.
+    not_in_subset: HashSet<char, FxBuildHasher>,
+    /// With when each was last used.
+    layouts: HashMap<LayoutKey, (Rc<FormattedTextBlock>, u64), FxBuildHasher>,
+    /// The keys of `layouts` ordered by when they were last used.
+    by_use: BTreeMap<u64, LayoutKey>,
+    /// Counts lookups, to order them.
+    uses: u64,
+    stats: CacheStats,
 }
 
 impl TextCache {
+    /// The most layouts kept, which is enough for a few screens of text,
+    /// while layouts at outdated sizes are soon evicted after resizing.
+    const CAPACITY: usize = 256;
+
     fn new() -> Self {
+        let subset = Font::new(include_bytes!("../../font/font.ttf")).expect("Parsing font");
         TextCache {
-            font: Font::new(include_bytes!("../../font/font.ttf")).expect("Parsing font"),
-            statics: HashMap::default(),
+            fonts: FontFamily::new(vec![subset.clone()]),
+            subset,
+            full: None,
+            not_in_subset: HashSet::default(),
+            layouts: HashMap::default(),
+            by_use: BTreeMap::new(),
+            uses: 0,
+            stats: CacheStats::default(),
+        }
+    }
+    /// Get the layout of a line of text,
+    /// or of a text box if `wrap` has the box's width in pixels.
+    fn get(&mut self,  text: Cow<'static, str>,  scaled_size: f32,  wrap: Option<(f32, TextBox)>)
+    -> Rc<FormattedTextBlock> {
+        let key = LayoutKey {
+            text,
+            size: scaled_size.to_bits(),
+            wrap: wrap.map(|(max_width, layout)| {
+                (max_width.to_bits(), layout.line_spacing.to_bits(), layout.align as i8)
+            }),
+        };
+        self.uses += 1;
+        if let Some((layout, last_use)) = self.layouts.get_mut(&key) {
+            self.stats.hits += 1;
+            let key = self.by_use.remove(last_use).expect("layouts and by_use have the same keys");
+            self.by_use.insert(self.uses, key);
+            *last_use = self.uses;
+            return layout.clone();
         }
+        self.stats.misses += 1;
+        let options = match wrap {
+            Some((max_width, layout)) => text_box_options(max_width, layout),
+            None => TextOptions::new(),
+        };
+        self.check_subset(&key.text);
+        let layout = Rc::new(self.fonts.layout_text(&key.text, scaled_size, options));
+        self.keep(key, layout.clone());
+        layout
     }
-    fn create(&self,  text: &str,  scaled_size: f32) -> Rc<FormattedTextBlock> {
-        Rc::new(self.font.layout_text(text, scaled_size, TextOptions::new()))
+
+    /// Add a new layout, and remove the least recently used one if there are too many.
+    fn keep(&mut self,  key: LayoutKey,  layout: Rc<FormattedTextBlock>) {
+        if self.layouts.len() >= Self::CAPACITY {
+            if let Some((_, oldest)) = self.by_use.pop_first() {
+                self.layouts.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+        }
+        self.by_use.insert(self.uses, key.clone());
+        self.layouts.insert(key, (layout, self.uses));
     }
-    fn get_static(&mut self,  text: &'static str,  scaled_size: f32) -> Rc<FormattedTextBlock> {
-        let key = (text, scaled_size as i32);
-        self.statics.entry(key).or_insert_with(|| {
-            Rc::new(self.font.layout_text(text, scaled_size, TextOptions::new()))
-        }).clone()
+
+    fn len(&self) -> usize {
+        self.layouts.len()
+    }
+
+    /// Report characters that the subset doesn't have the first time they're used,
+    /// and start using the full font for them if there is one.
+    fn check_subset(&mut self,  text: &str) {
+        let mut missing = String::new();
+        for c in text.chars() {
+            if !c.is_control()
+            && self.subset.lookup_glyph_for_codepoint(c).is_none()
+            && self.not_in_subset.insert(c) {
+                missing.push(c);
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+        eprintln!("{:?} in {:?} are not in font/font.ttf", missing, text);
+        if self.full.is_none() {
+            if let Some(bytes) = FULL_FONT {
+                let full = Font::new(bytes).expect("Parsing font");
+                self.fonts = FontFamily::new(vec![self.subset.clone(), full.clone()]);
+                self.full = Some(full);
+            }
+        }
+        match &self.full {
+            Some(full) => {
+                let in_no_font = missing.chars()
+                    .filter(|&c| full.lookup_glyph_for_codepoint(c).is_none())
+                    .collect::<String>();
+                if !in_no_font.is_empty() {
+                    eprintln!("and {:?} are not in font/FiraSans-Regular.ttf either", in_no_font);
+                }
+            }
+            None => eprintln!("and will not be drawn"),
+        }
+    }
+}
+
+/// Decoded textures by name, or `None` if decoding failed.
+#[derive(Default)]
+struct ImageCache {
+    images: HashMap<String, Option<ImageHandle>, FxBuildHasher>,
+}
+
+impl ImageCache {
+    fn get(&mut self,  texture: Texture,  g: &mut Graphics2D) -> Option<&ImageHandle> {
+        // not using entry() to not allocate the name every time
+        if !self.images.contains_key(texture.name) {
+            let image = g.create_image_from_file_bytes(
+                    None,
+                    ImageSmoothingMode::Linear,
+                    Cursor::new(texture.data),
+            );
+            let image = image.map_err(|e| {
+                // only reported once, because failures are cached too
+                eprintln!("Cannot load texture {}: {}", texture.name, e);
+            });
+            self.images.insert(texture.name.to_string(), image.ok());
+        }
+        self.images[texture.name].as_ref()
+    }
+}
+
+/// The part of the image to draw, in fractions of its size.
+fn normalized_source(sprite: Sprite,  image: &ImageHandle) -> Rectangle {
+    match sprite.source {
+        Some([x, y, w, h]) => {
+            let size = image.size().into_f32();
+            Rectangle::new(
+                Vector2 { x: x/size.x,  y: y/size.y },
+                Vector2 { x: (x+w)/size.x,  y: (y+h)/size.y },
+            )
+        }
+        None => Rectangle::new(Vector2::ZERO, Vector2::new(1.0, 1.0)),
     }
 }
 
@@ -121,16 +463,28 @@ struct GameWrapper<G: Game> {
     stopwatch: Stopwatch,
     last_physics: f64,
     shapes: Graphics,
-    text: TextCache,
+    /// Shared with the `FontMetrics` of `shapes`.
+    text: Rc<RefCell<TextCache>>,
+    images: ImageCache,
 }
 
-impl<G: Game> WindowHandler for GameWrapper<G> {
+impl<G: Game> GameWrapper<G> {
+    fn update(&mut self) {
+        let prev = self.last_physics;
+        self.last_physics = self.stopwatch.secs_elapsed();
+        let elapsed = self.last_physics - prev;
+        self.game.update(elapsed as f32);
+    }
+}
+
+impl<G: Game> WindowHandler<EngineEvent> for GameWrapper<G> {
     fn on_start(&mut self,
-            h: &mut WindowHelper<()>,
+            h: &mut WindowHelper<EngineEvent>,
             info: speedy2d::window::WindowStartupInfo
     ) {
         let size = info.viewport_size_pixels().into_f32();
         self.window_size = [size.x, size.y];
+        self.game.resize(self.window_size);
         h.set_cursor_visible(true);
         if let Err(e) = h.set_cursor_grab(false) {
             match e.cause() {
@@ -139,6 +493,9 @@ impl<G: Game> WindowHandler for GameWrapper<G> {
             }
         }
 
+        #[cfg(target_arch="wasm32")]
+        listen_for_touch(h.create_user_event_sender());
+
         // icon is not used in wasm, and threads don't work there.
         #[cfg(not(target_arch="wasm32"))]
         {
@@ -151,29 +508,59 @@ impl<G: Game> WindowHandler for GameWrapper<G> {
             let sender = h.create_user_event_sender();
             thread::spawn(move || {
                 loop {
-                    sender.send_event(()).unwrap();
+                    sender.send_event(EngineEvent::Update).unwrap();
                     thread::sleep(Duration::from_secs_f32((UPDATE_RATE as f32).recip()));
                 }
             });
+
+            for &path in self.game.watched_files() {
+                let sender = h.create_user_event_sender();
+                thread::spawn(move || {
+                    watch(Path::new(path), SETTLE, &mut|| {
+                        sender.send_event(EngineEvent::FileChanged(path)).unwrap();
+                    });
+                });
+            }
         }
     }
 
-    fn on_user_event(&mut self,  _: &mut WindowHelper<()>,  _: ()) {
-        let prev = self.last_physics;
-        self.last_physics = self.stopwatch.secs_elapsed();
-        let elapsed = self.last_physics - prev;
-        self.game.update(elapsed as f32);
+    fn on_user_event(&mut self,  _: &mut WindowHelper<EngineEvent>,  event: EngineEvent) {
+        match event {
+            #[cfg(not(target_arch="wasm32"))]
+            EngineEvent::Update => self.update(),
+            #[cfg(not(target_arch="wasm32"))]
+            EngineEvent::FileChanged(path) => self.game.file_changed(path),
+            #[cfg(target_arch="wasm32")]
+            EngineEvent::Touch { id, phase, pos } => {
+                let (offset, scale) = letterbox_and_scale(self.window_size);
+                let pos = (pos - offset) / scale;
+                self.game.touch(id, phase, [pos.x, pos.y]);
+            }
+        }
     }
 
-    fn on_draw(&mut self,  h: &mut WindowHelper<()>,  g: &mut Graphics2D) {
+    fn on_draw(&mut self,  h: &mut WindowHelper<EngineEvent>,  g: &mut Graphics2D) {
         #[cfg(target_arch="wasm32")]
-        self.on_user_event(h, ());
+        self.update();
 
         g.clear_screen(spColor::BLACK);
         self.game.render(&mut self.shapes);
+        let mut text_cache = self.text.borrow_mut();
 
         let (offset, scale) = letterbox_and_scale(self.window_size);
-        for shape in self.shapes.drain() {
+        let show_stats = self.shapes.is_visible(Layer::Debug);
+        let mut current_clip = None;
+        for (clip, shape) in self.shapes.drain() {
+            if clip != current_clip {
+                current_clip = clip;
+                g.set_clip(clip.map(|[x, y, w, h]| {
+                    let to_pixel = |point: Vector2<f32>| {
+                        let point = point*scale + offset;
+                        Vector2::new(point.x.round() as i32, point.y.round() as i32)
+                    };
+                    Rectangle::new(to_pixel(Vector2 { x, y }), to_pixel(Vector2 { x: x+w,  y: y+h }))
+                }));
+            }
             match shape {
                 Shape::Line { color, width, area } => {
                     let start = Vector2::new(area[0],  area[1])*scale+offset;
@@ -195,66 +582,150 @@ impl<G: Game> WindowHandler for GameWrapper<G> {
                     let color = map_color(color);
                     g.draw_circle(center, radius, color);
                 }
-                Shape::StaticText{ color, size, position, center, text } => {
-                    let text = self.text.get_static(text, size * scale);
-                    let mut position = Vector2 { x: position[0], y: position[1] } * scale + offset;
-                    position.x = match center[0] {
-                        Align::Left => position.x,
-                        Align::Center => position.x - text.width()/2.0,
-                        Align::Right => position.x - text.width(),
-                    };
-                    position.y = match center[1] {
-                        Align::Left => position.y,
-                        Align::Center => position.y - text.height()/2.0,
-                        Align::Right => position.y - text.height(),
-                    };
+                Shape::Polygon{ color, points } => {
+                    let points = points.iter()
+                        .map(|&[x, y]| Vector2 { x, y }*scale + offset)
+                        .collect::<Vec<_>>();
+                    g.draw_polygon(&Polygon::new(&points), Vector2::ZERO, map_color(color));
+                }
+                Shape::TriangleFan{ color, points } => {
                     let color = map_color(color);
-                    g.draw_text(position, color, &text);
+                    let points = points.iter()
+                        .map(|&[x, y]| Vector2 { x, y }*scale + offset)
+                        .collect::<Vec<_>>();
+                    if let Some((&first, rest)) = points.split_first() {
+                        for pair in rest.windows(2) {
+                            g.draw_triangle([first, pair[0], pair[1]], color);
+                        }
+                    }
                 }
-                Shape::DynamicText{ color, size, position, center, text } => {
-                    let text = self.text.create(&text, size * scale);
-                    let mut position = Vector2 { x: position[0], y: position[1] } * scale + offset;
-                    position.x = match center[0] {
-                        Align::Left => position.x,
-                        Align::Center => position.x - text.width()/2.0,
-                        Align::Right => position.x - text.width(),
-                    };
-                    position.y = match center[1] {
-                        Align::Left => position.y,
-                        Align::Center => position.y - text.height()/2.0,
-                        Align::Right => position.y - text.height(),
-                    };
+                Shape::Ellipse{ color, area } => {
+                    let points = Outline::Ellipse{ area }.points(0.0).iter()
+                        .map(|&[x, y]| Vector2 { x, y }*scale + offset)
+                        .collect::<Vec<_>>();
+                    g.draw_polygon(&Polygon::new(&points), Vector2::ZERO, map_color(color));
+                }
+                Shape::RoundedRectangle{ color, area, radius } => {
+                    let radius = radius.min(area[2]/2.0).min(area[3]/2.0);
+                    let rect = RoundedRectangle::new(
+                        Vector2 { x: area[0],  y: area[1] }*scale + offset,
+                        Vector2 { x: area[0]+area[2],  y: area[1]+area[3] }*scale + offset,
+                        radius * scale,
+                    );
+                    g.draw_rounded_rectangle(rect, map_color(color));
+                }
+                Shape::Arc{ color, width, center, radius, angles } => {
+                    let color = map_color(color);
+                    for quad in arc_quads(width, center, radius, angles) {
+                        g.draw_quad(quad.map(|[x, y]| Vector2 { x, y }*scale + offset), color);
+                    }
+                }
+                Shape::Stroke{ color, width, outline } => {
                     let color = map_color(color);
-                    g.draw_text(position, color, &text);
+                    for quad in outline.stroke(width) {
+                        g.draw_quad(quad.map(|[x, y]| Vector2 { x, y }*scale + offset), color);
+                    }
+                }
+                Shape::Image{ tint, sprite, area } => {
+                    if let Some(image) = self.images.get(sprite.texture, g) {
+                        let rect = Rectangle::new(
+                            Vector2 { x: area[0],  y: area[1] }*scale + offset,
+                            Vector2 { x: area[0]+area[2],  y: area[1]+area[3] }*scale + offset,
+                        );
+                        let source = normalized_source(sprite, image);
+                        g.draw_rectangle_image_subset_tinted(rect, map_color(tint), source, image);
+                    }
+                }
+                Shape::ImageQuad{ tint, sprite, corners } => {
+                    if let Some(image) = self.images.get(sprite.texture, g) {
+                        let source = normalized_source(sprite, image);
+                        let (top_left, bottom_right) = (*source.top_left(), *source.bottom_right());
+                        let source = [
+                            top_left,
+                            Vector2::new(bottom_right.x, top_left.y),
+                            bottom_right,
+                            Vector2::new(top_left.x, bottom_right.y),
+                        ];
+                        let corners = corners.map(|[x, y]| Vector2 { x, y }*scale + offset);
+                        g.draw_quad_image_tinted_four_color(corners, [map_color(tint); 4], source, image);
+                    }
+                }
+                Shape::Gradient{ gradient, points } => {
+                    draw_triangles(g, &gradient.triangles(&points), offset, scale);
+                }
+                Shape::Triangles{ vertices } => {
+                    draw_triangles(g, &vertices, offset, scale);
+                }
+                Shape::StaticText{ color, size, position, center, text } => {
+                    let text = text_cache.get(Cow::Borrowed(text), size * scale, None);
+                    let position = Vector2 { x: position[0], y: position[1] } * scale + offset;
+                    let position = align_text(position, &text, text.width(), center);
+                    g.draw_text(position, map_color(color), &text);
+                }
+                Shape::DynamicText{ color, size, position, center, text } => {
+                    let text = text_cache.get(Cow::Owned(text), size * scale, None);
+                    let position = Vector2 { x: position[0], y: position[1] } * scale + offset;
+                    let position = align_text(position, &text, text.width(), center);
+                    g.draw_text(position, map_color(color), &text);
+                }
+                Shape::StaticTextBox{ color, size, position, layout, text } => {
+                    let max_width = layout.max_width * scale;
+                    let text = text_cache.get(Cow::Borrowed(text), size * scale, Some((max_width, layout)));
+                    let position = Vector2 { x: position[0], y: position[1] } * scale + offset;
+                    let position = align_text(position, &text, max_width, (layout.align, layout.valign));
+                    g.draw_text(position, map_color(color), &text);
+                }
+                Shape::DynamicTextBox{ color, size, position, layout, text } => {
+                    let max_width = layout.max_width * scale;
+                    let text = text_cache.get(Cow::Owned(text), size * scale, Some((max_width, layout)));
+                    let position = Vector2 { x: position[0], y: position[1] } * scale + offset;
+                    let position = align_text(position, &text, max_width, (layout.align, layout.valign));
+                    g.draw_text(position, map_color(color), &text);
                 }
             }
         }
+        g.set_clip(None);
+        if show_stats {
+            // laid out without the cache, so that this text, which changes every frame, isn't counted in it
+            let stats = format!("text layouts: {}, {}, {} characters not in font subset",
+                text_cache.len(), text_cache.stats, text_cache.not_in_subset.len()
+            );
+            let stats = text_cache.fonts.layout_text(&stats, 0.025*scale, TextOptions::new());
+            g.draw_text(Vector2::ZERO, map_color(DEBUG_TEXT_COLOR), &stats);
+        }
 
         // Required to make the screen update.
         // Surprisingly doesn't cause 100% CPU usage.
         h.request_redraw();
     }
 
-    fn on_resize(&mut self,  _: &mut WindowHelper<()>,  size: speedy2d::dimen::UVec2) {
+    fn on_resize(&mut self,  _: &mut WindowHelper<EngineEvent>,  size: speedy2d::dimen::UVec2) {
         self.window_size[0] = size.into_f32().x;
         self.window_size[1] = size.into_f32().y;
+        self.game.resize(self.window_size);
     }
 
-    fn on_mouse_move(&mut self,  _: &mut WindowHelper<()>,  pos: Vector2<f32>) {
+    fn on_mouse_move(&mut self,  _: &mut WindowHelper<EngineEvent>,  pos: Vector2<f32>) {
         let (offset, scale) = letterbox_and_scale(self.window_size);
         let pos = (pos - offset) / scale;
         self.game.mouse_move([pos.x, pos.y]);
     }
 
-    fn on_mouse_button_down(&mut self,  _: &mut WindowHelper<()>,  button: spMouseButton) {
+    fn on_mouse_button_down(&mut self,  _: &mut WindowHelper<EngineEvent>,  button: spMouseButton) {
         if let Some(button) = map_button(button) {
             self.game.mouse_press(button);
         }
     }
 
+    fn on_mouse_button_up(&mut self,  _: &mut WindowHelper<EngineEvent>,  button: spMouseButton) {
+        if let Some(button) = map_button(button) {
+            self.game.mouse_release(button);
+        }
+    }
+
     fn on_key_down(
             &mut self,
-            _: &mut WindowHelper<()>,
+            _: &mut WindowHelper<EngineEvent>,
             key: Option<VirtualKeyCode>,
             _: speedy2d::window::KeyScancode
     ) {
@@ -265,7 +736,7 @@ impl<G: Game> WindowHandler for GameWrapper<G> {
 
     fn on_key_up(
             &mut self,
-            _: &mut WindowHelper<()>,
+            _: &mut WindowHelper<EngineEvent>,
             key: Option<VirtualKeyCode>,
             _: speedy2d::window::KeyScancode
     ) {
@@ -279,19 +750,23 @@ impl<G: Game> WindowHandler for GameWrapper<G> {
 
 #[inline(never)]
 pub fn start<G:Game+'static>(game: G,  name: &'static str,  initial_size: [f32; 2]) {
+    let text = Rc::new(RefCell::new(TextCache::new()));
+    let mut shapes = Graphics::default();
+    shapes.set_text_metrics(Box::new(FontMetrics(text.clone())));
     let wrapper = GameWrapper {
         game,
         window_size: initial_size,
         stopwatch: Stopwatch::new().expect("create stopwatch"),
         last_physics: 0.0,
-        shapes: Graphics::default(),
-        text: TextCache::new(),
+        shapes,
+        text,
+        images: ImageCache::default(),
     };
 
     #[cfg(target_arch="wasm32")]
     {
         let _ = name;
-        WebCanvas::new_for_id("space_tennis_game", wrapper)
+        WebCanvas::<EngineEvent>::new_for_id_with_user_events(CANVAS_ID, wrapper)
             .expect("bind to canvas");
         // .unregister_when_dropped() would make the game end immediately.
     }
@@ -305,7 +780,7 @@ pub fn start<G:Game+'static>(game: G,  name: &'static str,  initial_size: [f32;
                 .with_resizable(true)
                 .with_transparent(false)
                 .with_vsync(true);
-        match Window::new_with_options(name, options) {
+        match Window::<EngineEvent>::new_with_user_events(name, options) {
             Ok(window) => window.run_loop(wrapper),
             Err(e) => panic!("creating window failed: {:#?}", e),
         }
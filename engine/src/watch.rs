@@ -0,0 +1,111 @@
+/* Copyright 2018, 2023-2024 Torbjørn Birch Moltu
+ *
+ * This file is part of space_tennis.
+ * You can redistribute it and/or modify it under the terms of the
+ * GNU General Public License as published by the Free Software Foundation,
+ * either version 3 of the License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+extern crate notify;
+
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use notify::{recommended_watcher, Watcher, Error, RecursiveMode};
+use notify::event::{Event, EventKind};
+
+/// When `watch()` calls the function after something changed.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub struct Delay {
+    /// Wait until nothing has changed for this long.
+    /// Saving a file can be several events, and they should be handled together
+    /// and not before the file is completely written.
+    pub settle: Duration,
+    /// Ignore changes for this long after one that was not ignored.
+    pub at_most_every: Duration,
+}
+
+/// For files that are read by the game.
+pub const SETTLE: Delay = Delay { settle: Duration::from_millis(200),  at_most_every: Duration::ZERO };
+
+/// Watch a directory, or a single file, for changes,
+/// and call a function after them as decided by `delay`.
+///
+/// Files are watched through the directory they're in,
+/// because many editors save by replacing the file.
+/// Never returns unless watching fails, so it should be run on its own thread.
+pub fn watch(path: &Path,  delay: Delay,  callback: &mut dyn FnMut()) {
+    let (dir, file) = match (path.is_dir(), path.parent(), path.file_name()) {
+        (true, _, _) | (false, _, None) => (path, None),
+        (false, Some(parent), Some(file)) if parent != Path::new("") => (parent, Some(file.to_owned())),
+        (false, _, Some(file)) => (Path::new("."), Some(file.to_owned())),
+    };
+    let (tx, rx) = mpsc::channel();
+    let mut last_forwarded = None::<Instant>;
+    let forwarder = move |event: Result<Event,Error>| {
+        match event {
+            Ok(Event { kind: EventKind::Access(_), .. }) => {},
+            // Ok(Event { kind: EventKind::Modify(Modify::Metatdata), .. }) => {},
+            Ok(ev) => {
+                let relevant = match &file {
+                    Some(file) => ev.paths.iter().any(|path| path.file_name() == Some(file)),
+                    None => true,
+                };
+                if relevant {
+                    #[cfg(feature="dyn")]
+                    eprintln!("fs event: {:?}", ev);
+                    let now = Instant::now();
+                    let skip = last_forwarded.is_some_and(|last| {
+                        now.saturating_duration_since(last) < delay.at_most_every
+                    });
+                    if !skip {
+                        last_forwarded = Some(now);
+                        tx.send(()).unwrap();
+                    }
+                }
+            },
+            Err(e) => {
+                eprintln!("fs watch error: {} ({:?})", e, e.paths);
+            },
+        }
+    };
+    let mut watcher = match recommended_watcher(forwarder) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Cannot create fs watcher: {} - changes to {:?} will not be noticed", e, path);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+        eprintln!("Cannot watch {:?}: {} - changes to {:?} will not be noticed", dir, e, path);
+        return;
+    }
+
+    loop {
+        if let Err(e) = rx.recv() {
+            eprintln!("fs watcher channel receive error: {}, quitting", e);
+            return;
+        }
+        if !delay.settle.is_zero() {
+            loop {
+                match rx.recv_timeout(delay.settle) {
+                    Ok(()) => continue,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        eprintln!("fs watcher channel disconnected, quitting");
+                        return;
+                    }
+                }
+            }
+        }
+        callback();
+    }
+}
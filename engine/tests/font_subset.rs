@@ -0,0 +1,31 @@
+//! Checks that `font/font.ttf` has every character the translations use,
+//! so that they're not drawn with the full font.
+
+extern crate speedy2d;
+
+use speedy2d::font::{Font, TextLayout};
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn subset_has_every_character_in_the_catalogs() {
+    let subset = Font::new(include_bytes!("../../font/font.ttf")).expect("parse font/font.ttf");
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../locale");
+    let mut missing = Vec::new();
+    for entry in fs::read_dir(&dir).expect("read locale directory") {
+        let path = entry.expect("read locale directory").path();
+        let contents = fs::read_to_string(&path).expect("read catalog");
+        let messages = contents.lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .filter_map(|line| line.split_once('=').map(|(_, message)| message));
+        for c in messages.flat_map(str::chars) {
+            if subset.lookup_glyph_for_codepoint(c).is_none() {
+                missing.push(format!("{:?} in {}", c, path.display()));
+            }
+        }
+    }
+    missing.sort();
+    missing.dedup();
+    assert!(missing.is_empty(), "not in font/font.ttf:\n{}", missing.join("\n"));
+}
@@ -1,7 +1,10 @@
 #!/usr/bin/env bash
 # Reduce the size of a font by removing unneeded characters from it.
-# This can be done because this game won't take user input
-# and therefore won't need to display arbitrary characters.
+# This can be done because the only text players type is their name in uppercase ASCII,
+# so the game won't need to display arbitrary characters.
+# The subset has ASCII and the Norwegian letters æ, ø and å in both cases.
+# Characters not in the subset are drawn with the full font except in wasm builds,
+# and the speedy2d engine prints them, so that they can be added here.
 
 cd "$(dirname "$0")" || exit 1
 
@@ -12,4 +15,4 @@ if ! command -V pyftsubset; then
 fi
 
 exec pyftsubset FiraSans-Regular.ttf --output-file=font.ttf \
-     --unicodes=U+0020-007e
+     --unicodes=U+0020-007e,U+00C5,U+00C6,U+00D8,U+00E5,U+00E6,U+00F8
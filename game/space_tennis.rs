@@ -1,37 +1,86 @@
 use ::interface::game::*;
+use ::interface::input::*;
+use ::interface::camera::Camera;
+use ::interface::locale::{Catalog, Localization};
+use ::interface::storage::{self, load_migrated, save_versioned, Category, Storage};
+use std::env;
 use std::f32::consts::PI;
+use std::fmt::Write;
+use std::io;
+use std::mem;
+use std::path::Path;
+
+mod tuning;
+mod theme;
+mod touch;
+mod stats;
+mod records;
+use self::tuning::{Setting, Tuning, TUNING_FILE};
+use self::theme::{load_themes, Paint, Theme, THEMES_DIR};
+use self::touch::{TouchAction, Touches};
+use self::stats::{load_stats, save_stats, stats_csv, stats_json, MatchStats, Rally, Side};
 
 pub const NAME: &str = "space tennis";
 pub const INITIAL_SIZE: [f32;2] = [500.0, 500.0];
 
-const ARENA: [f32;3] = [1.0, 1.0, 2.0]; // 
-const BALL_RADIUS: f32 = 0.125; // exact representable
-const MISS_BALL_RADIUS: f32 = 0.025;
-const MAX_MISSES: u32 = (ARENA[1]/(3.0*MISS_BALL_RADIUS)) as u32;
-const RACKET_SIZE: [f32;2] = [0.22, 0.15];
-const PLAYER_MAX_SPEED: [f32;2] = [0.9, 0.9];
-const OPPONENT_MAX_SPEED: [f32;2] = [0.4, 0.4];
-const PLAYER_RESTART_DELAY: f32 = 0.2; // of ARENA[2]
-const OPPONENT_RESTART_DELAY: f32 = 0.3; // of ARENA[2]
-const BALL_START_ZSPEED: f32 = 0.6;
-const BALL_ZSPEED_LEVEL_ADD: f32 = 0.02;
-const BRACKET_SPEED_TRANSFER: f32 = 0.75; // based on mass of ball and bracket
-const FOV: f32 = PI/3.0; // 60°
-const FRONT_FILLS: f32 = 0.8; // of the screen
-//const BACK_FILLS: f32 = 0.3; // of the screen
-
-const WALL_COLOR: &str = "222332f8";
-const WALL_LINE_COLOR: &str = "008800";
-const WALL_LINES: u32 = 5;
-const LINE_WIDTH: f32 = 0.05;
-const LINE_WIDTH_EDGE: f32 = LINE_WIDTH*1.5;
-const RACKET_COLOR: &str = "ddddddaa";
-const RACKET_BORDER_COLOR: &str = "5555dd";
-const RACKET_BORDER_WIDTH: [f32;2] = [0.01333, 0.00666]; // [left/right, top/bottom]
-const BALL_COLOR: &str = "aaff55ee";
-const BALL_LINE_COLOR: &str = "eeeeee88";
-const MISS_COLOR: &str = "ff3333";
-const PAUSE_COLOR: &str = "888877aa";
+const SPIN_PER_DISTANCE: f32 = 2.0*PI; // radians the HUD ball rotates while the ball moves one unit
+const SPIN_INDICATOR_SIZE: f32 = 0.04; // of the screen
+const DEBUG_LOOKAHEAD: f32 = 0.5; // seconds
+/// The directory or prefix things are saved under.
+const STORAGE_NAME: &str = "space_tennis";
+// where these were saved in the working directory, before there was storage
+#[cfg(not(target_arch="wasm32"))]
+const BINDINGS_FILE: &str = "space_tennis_bindings.txt";
+#[cfg(not(target_arch="wasm32"))]
+const SETTINGS_FILE: &str = "space_tennis_settings.txt";
+/// The version of the format settings are saved in.
+const SETTINGS_VERSION: u32 = 1;
+/// The longest player name, which fits beside the longest values on the records screen.
+const NAME_LENGTH: usize = 12;
+
+// generated by images/create_textures.py
+const SPRITES: Texture = Texture {
+    name: "sprites",
+    data: include_bytes!("../images/sprites.png"),
+};
+const BALL_SPRITE: Sprite = SPRITES.sprite_at([0.0, 0.0, 64.0, 64.0]);
+const LOGO_SIZE: [f32;2] = [216.0, 24.0]; // pixels
+const LOGO_SPRITE: Sprite = SPRITES.sprite_at([0.0, 64.0, LOGO_SIZE[0], LOGO_SIZE[1]]);
+const BACKGROUND: Texture = Texture {
+    name: "stars",
+    data: include_bytes!("../images/stars.png"),
+};
+// the first is the default
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../locale/en.txt")),
+    ("nb", include_str!("../locale/nb.txt")),
+];
+/// Multipliers for the ball radius that the larger ball assist cycles through.
+const BALL_SIZES: [f32; 4] = [1.0, 1.25, 1.5, 2.0];
+const LOGO_WIDTH: f32 = 0.6; // of the screen
+const MESSAGE_MARGIN: f32 = 0.05; // of the screen, on each side
+
+/// Options that make the game easier to see and play.
+#[derive(Clone,Copy, Debug, PartialEq)]
+struct Accessibility {
+    /// Make foreground colors stand out from the background in any theme.
+    high_contrast: bool,
+    /// Draw the misses of the player as crosses and those of the opponent as diamonds,
+    /// instead of as the same balls.
+    miss_shapes: bool,
+    /// How much larger the ball is than in the tuning file, one of `BALL_SIZES`.
+    ball_size: f32,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Accessibility { high_contrast: false,  miss_shapes: false,  ball_size: 1.0 }
+    }
+}
+
+/// How a miss is drawn.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+enum MissShape {Ball, Cross, Diamond}
 
 fn clamp(p: f32,  (min,max): (f32,f32)) -> f32 {
          if p <= min   {min}
@@ -48,62 +97,508 @@ struct Keys {
     right: bool,
 }
 
+impl Keys {
+    /// -1, 0 or 1 for each axis.
+    fn direction(self) -> [f32; 2] {
+        [
+            self.right as i8 as f32 - self.left as i8 as f32,
+            self.down as i8 as f32 - self.up as i8 as f32,
+        ]
+    }
+}
+
+/// Which input moves the player racket, decided by what was used last.
 #[derive(Clone,Copy, PartialEq,Eq)]
-enum State {Playing, Paused, PlayerStart, OpponentStart}
+enum Control {
+    /// The racket follows the mouse or a finger.
+    Pointer,
+    /// The racket accelerates while direction keys are held.
+    Keyboard,
+}
+
+/// Change the speed along one axis towards `direction*max_speed`,
+/// but not faster than the acceleration or deceleration allows.
+fn accelerate(speed: f32,  direction: f32,  max_speed: f32,  dt: f32,  tuning: &Tuning) -> f32 {
+    let Tuning { keyboard_acceleration, keyboard_deceleration, .. } = *tuning;
+    if direction == 0.0 {
+        // slow down, but don't reverse
+        let slowed = speed.abs() - keyboard_deceleration*dt;
+        slowed.max(0.0).copysign(speed)
+    } else {
+        let rate = if speed*direction < 0.0 {
+            // braking while accelerating the other way
+            keyboard_acceleration + keyboard_deceleration
+        } else {
+            keyboard_acceleration
+        };
+        clamp(speed + direction*rate*dt, (-max_speed, max_speed))
+    }
+}
+
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+enum State {Playing, Paused, PlayerStart, OpponentStart, Settings, Records, Summary}
+
+impl State {
+    /// The states that can happen during a match, whose time is in `MatchStats`.
+    const TIMED: [State; 5] = [
+        State::Playing,
+        State::Paused,
+        State::PlayerStart,
+        State::OpponentStart,
+        State::Settings,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            State::Playing => "playing",
+            State::Paused => "paused",
+            State::PlayerStart => "player_start",
+            State::OpponentStart => "opponent_start",
+            State::Settings => "settings",
+            State::Records => "records",
+            State::Summary => "summary",
+        }
+    }
+}
+
+/// The screen for changing which keys and buttons do what.
+#[derive(Clone,Copy)]
+struct SettingsMenu {
+    /// The state to go back to when closed.
+    previous: State,
+    /// Index of a `SettingsRow`.
+    selected: usize,
+    /// Waiting for an input to bind to the selected action.
+    rebinding: bool,
+    /// Typing a new player name.
+    editing_name: bool,
+}
+
+/// A line in the settings screen.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+enum SettingsRow {
+    Binding(Action),
+    Player,
+    Records,
+    Language,
+    Theme,
+    HighContrast,
+    MissShapes,
+    BallSize,
+}
+
+/// The rows after the actions.
+const SETTINGS_OPTIONS: [SettingsRow; 7] = [
+    SettingsRow::Player,
+    SettingsRow::Records,
+    SettingsRow::Language,
+    SettingsRow::Theme,
+    SettingsRow::HighContrast,
+    SettingsRow::MissShapes,
+    SettingsRow::BallSize,
+];
+const SETTINGS_ROWS: usize = Action::ALL.len() + SETTINGS_OPTIONS.len();
+
+impl SettingsRow {
+    fn at(index: usize) -> Self {
+        match Action::ALL.get(index) {
+            Some(&action) => SettingsRow::Binding(action),
+            None => SETTINGS_OPTIONS[index - Action::ALL.len()],
+        }
+    }
+}
+
+/// The key of the action's label in the locale files.
+fn action_key(action: Action) -> &'static str {
+    match action {
+        Action::MoveUp => "action.MoveUp",
+        Action::MoveDown => "action.MoveDown",
+        Action::MoveLeft => "action.MoveLeft",
+        Action::MoveRight => "action.MoveRight",
+        Action::Serve => "action.Serve",
+        Action::Pause => "action.Pause",
+        Action::Settings => "action.Settings",
+        Action::Debug => "action.Debug",
+    }
+}
+
+/// The key of the value of an option that can be on or off.
+fn on_off(on: bool) -> &'static str {
+    if on {"controls.on"} else {"controls.off"}
+}
+
+fn input_label(input: Input,  strings: &Localization) -> String {
+    match input {
+        Input::Key(Key::Letter(c)) => c.to_string(),
+        Input::Key(key) => format!("{:?}", key),
+        Input::Mouse(button) => strings.format("input.mouse", &[("button", &format!("{:?}", button))]),
+        Input::Gamepad(button) => strings.format("input.gamepad", &[("button", &format!("{:?}", button))]),
+    }
+}
+
+/// All locales, using the one in `LANG` if there is one for it.
+fn load_strings() -> Localization {
+    let (default, source) = LOCALES[0];
+    let mut strings = Localization::new(Catalog::parse(default, source).expect("parse default locale"));
+    for &(locale, source) in &LOCALES[1..] {
+        match Catalog::parse(locale, source) {
+            Ok(catalog) => strings.add(catalog),
+            Err(e) => eprintln!("Cannot parse locale/{}.txt: {}", locale, e),
+        }
+    }
+    if let Ok(lang) = env::var("LANG") {
+        strings.set_locale(&lang);
+    }
+    strings
+}
+
+/// Storage with the files that were saved in the working directory before there was storage.
+fn open_storage() -> Box<dyn Storage> {
+    #[allow(unused_mut)]
+    let mut storage = storage::open(STORAGE_NAME);
+    #[cfg(not(target_arch="wasm32"))]
+    for (name, file) in [("bindings", BINDINGS_FILE), ("settings", SETTINGS_FILE)] {
+        match storage::import_file(&mut *storage, Category::Config, name, Path::new(file)) {
+            Ok(true) => eprintln!("Imported {} into storage", file),
+            Ok(false) => {}
+            Err(e) => eprintln!("Cannot import {} into storage: {}", file, e),
+        }
+    }
+    storage
+}
+
+/// Change settings saved in `version` to the format of the next version.
+fn migrate_settings(version: u32,  contents: String) -> Result<String, String> {
+    match version {
+        // SETTINGS_FILE from before there was storage, which has the same format
+        0 => Ok(contents),
+        _ => Err(format!("unknown version {}", version)),
+    }
+}
+
+fn load_bindings(storage: &dyn Storage) -> Bindings {
+    match Bindings::load(storage) {
+        Ok(Some(bindings)) => bindings,
+        Ok(None) => Bindings::default(),
+        Err(e) => {
+            eprintln!("Cannot load key bindings: {}", e);
+            Bindings::default()
+        }
+    }
+}
+
+/// `name` if it can be a player name:
+/// uppercase ASCII letters and spaces, which are what can be typed on the settings screen.
+fn valid_player_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.len() <= NAME_LENGTH
+        && name.chars().all(|c| c.is_ascii_uppercase() || c == ' ');
+    if valid {Some(name.to_string())} else {None}
+}
+
+/// The player name to use before one is chosen, based on the login name.
+fn default_player_name() -> String {
+    let login = env::var("USER").or_else(|_| env::var("USERNAME")).unwrap_or_default();
+    let name = login.to_ascii_uppercase().chars()
+        .filter(|c| c.is_ascii_uppercase())
+        .take(NAME_LENGTH)
+        .collect::<String>();
+    if name.is_empty() {"PLAYER".to_string()} else {name}
+}
+
+/// Handle the command line options that don't start the game,
+/// and return the exit code if the game shouldn't start.
+pub fn run_command_line(args: &[String]) -> Option<i32> {
+    match args {
+        [option, format] if option == "--export-stats" && (format == "json" || format == "csv") => {
+            let all = match load_stats(&*storage::open(STORAGE_NAME)) {
+                Ok(all) => all,
+                Err(e) => {
+                    eprintln!("Cannot load match statistics: {}", e);
+                    return Some(1);
+                }
+            };
+            match format.as_str() {
+                "json" => print!("{}", stats_json(&all)),
+                _ => print!("{}", stats_csv(&all)),
+            }
+            Some(0)
+        }
+        [option, ..] if option == "--export-stats" => {
+            eprintln!("Usage: space_tennis --export-stats json|csv");
+            Some(2)
+        }
+        _ => None,
+    }
+}
 
 pub struct SpaceTennis {
     ball_pos: [f32; 3],
     ball_vel: [f32; 3],
     player_pos: [f32; 2],
     keys: Keys,
+    control: Control,
+    keyboard_speed: [f32; 2],
     player_target: [f32; 2],
+    touches: Touches,
+    bindings: Bindings,
+    storage: Box<dyn Storage>,
+    settings: SettingsMenu,
+    /// The state to go back to when the records screen is closed.
+    records_return: State,
+    player: String,
+    /// Of the current match.
+    stats: MatchStats,
+    rally: Rally,
+    /// Of every finished match, which the records are found from.
+    stats_log: Vec<MatchStats>,
+    /// False if the saved statistics could not be loaded, so that they're not replaced.
+    can_save_stats: bool,
+    strings: Localization,
+    camera: Camera,
     player_misses: u32,
     opponent_pos: [f32; 2],
     opponent_target: [f32; 2],
     opponent_misses: u32,
-    state: State
+    state: State,
+    /// With `accessibility` applied.
+    tuning: Tuning,
+    /// As in the tuning file.
+    loaded_tuning: Tuning,
+    themes: Vec<Theme>,
+    /// Index into `themes`.
+    theme: usize,
+    /// `themes[theme]`, with high contrast applied if enabled.
+    current_theme: Theme,
+    accessibility: Accessibility,
+    debug: bool,
+    /// Rotation of the ball in the HUD, in radians.
+    ball_spin: f32,
 }
 
 impl SpaceTennis {
-    pub fn new() -> Self {SpaceTennis {
-        player_misses: 0,
-        opponent_misses: 0,
-        player_pos: [ARENA[0]/2.0, ARENA[1]/2.0],
-        keys: Keys::default(),
-        player_target: [ARENA[0]/2.0, ARENA[1]/2.0],
-        opponent_pos: [ARENA[0]/2.0, ARENA[1]/2.0],
-        opponent_target: [ARENA[0]/2.0, ARENA[1]/2.0],
-        ball_vel: [0.0, 0.0, BALL_START_ZSPEED],
-        ball_pos: [ARENA[0]/2.0, ARENA[1]/2.0, BALL_RADIUS],// at player
-        state: State::PlayerStart
-    } }
+    pub fn new() -> Self {
+        let tuning = Tuning::load().unwrap_or_else(|e| {
+            eprintln!("Cannot load {}: {}", TUNING_FILE, e);
+            Tuning::built_in()
+        });
+        let arena = tuning.arena;
+        let storage = open_storage();
+        let (stats_log, can_save_stats) = match load_stats(&*storage) {
+            Ok(stats_log) => (stats_log, true),
+            Err(e) => {
+                eprintln!("Cannot load match statistics: {} - new statistics will not be saved", e);
+                (Vec::new(), false)
+            }
+        };
+        let themes = load_themes();
+        let mut game = SpaceTennis {
+            player_misses: 0,
+            opponent_misses: 0,
+            player_pos: [arena[0]/2.0, arena[1]/2.0],
+            keys: Keys::default(),
+            control: Control::Pointer,
+            keyboard_speed: [0.0, 0.0],
+            player_target: [arena[0]/2.0, arena[1]/2.0],
+            touches: Touches::default(),
+            bindings: load_bindings(&*storage),
+            storage,
+            strings: load_strings(),
+            settings: SettingsMenu {
+                previous: State::PlayerStart,
+                selected: 0,
+                rebinding: false,
+                editing_name: false,
+            },
+            records_return: State::PlayerStart,
+            player: default_player_name(),
+            stats: MatchStats::default(),
+            rally: Rally::serve(Side::Player),
+            stats_log,
+            can_save_stats,
+            camera: {
+                let mut camera = Camera::default();
+                camera.resize(INITIAL_SIZE);
+                tuning.place_camera(&mut camera);
+                camera
+            },
+            opponent_pos: [arena[0]/2.0, arena[1]/2.0],
+            opponent_target: [arena[0]/2.0, arena[1]/2.0],
+            ball_vel: [0.0, 0.0, tuning.ball_start_zspeed],
+            ball_pos: [arena[0]/2.0, arena[1]/2.0, tuning.ball_radius],// at player
+            state: State::PlayerStart,
+            debug: false,
+            ball_spin: 0.0,
+            tuning,
+            loaded_tuning: tuning,
+            current_theme: themes[0].clone(),
+            themes,
+            theme: 0,
+            accessibility: Accessibility::default(),
+        };
+        match load_migrated(&*game.storage, Category::Config, "settings", SETTINGS_VERSION, migrate_settings) {
+            Ok(Some(contents)) => {
+                if let Err(e) = game.apply_settings(&contents) {
+                    eprintln!("Cannot load settings: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Cannot load settings: {}", e),
+        }
+        game
+    }
+
+    /// Use the settings in the format `save_settings()` writes.
+    ///
+    /// Nothing is changed if there is an error.
+    fn apply_settings(&mut self,  contents: &str) -> Result<(), String> {
+        let mut locale = None;
+        let mut player = None;
+        let mut theme = self.theme;
+        let mut accessibility = self.accessibility;
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (setting, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: missing =", i+1))?;
+            let (setting, value) = (setting.trim(), value.trim());
+            let line_error = |e: String| format!("line {}: {}", i+1, e);
+            match setting {
+                "locale" => locale = Some(value),
+                "player" => {
+                    player = Some(valid_player_name(value)
+                        .ok_or_else(|| line_error(format!("{:?} is not a valid name", value)))?);
+                }
+                "theme" => {
+                    theme = self.themes.iter().position(|theme| theme.name == value)
+                        .ok_or_else(|| line_error(format!("unknown theme {:?}", value)))?;
+                }
+                "high_contrast" => accessibility.high_contrast = bool::parse(value).map_err(line_error)?,
+                "miss_shapes" => accessibility.miss_shapes = bool::parse(value).map_err(line_error)?,
+                "ball_size" => {
+                    accessibility.ball_size = f32::parse(value).map_err(line_error)?;
+                    if !BALL_SIZES.contains(&accessibility.ball_size) {
+                        return Err(line_error(format!("ball_size must be one of {:?}", BALL_SIZES)));
+                    }
+                }
+                _ => return Err(line_error(format!("unknown setting {:?}", setting))),
+            }
+        }
+        if let Some(locale) = locale {
+            self.strings.set_locale(locale);
+        }
+        if let Some(player) = player {
+            self.player = player;
+        }
+        self.theme = theme;
+        self.accessibility = accessibility;
+        self.update_theme();
+        self.retune(self.loaded_tuning);
+        Ok(())
+    }
+
+    /// Save the choices on the settings screen other than bindings.
+    fn save_settings(&mut self) -> io::Result<()> {
+        let mut contents = String::new();
+        writeln!(contents, "locale = {}", self.strings.locale()).unwrap();
+        writeln!(contents, "player = {}", self.player).unwrap();
+        writeln!(contents, "theme = {}", self.themes[self.theme].name).unwrap();
+        writeln!(contents, "high_contrast = {}", self.accessibility.high_contrast).unwrap();
+        writeln!(contents, "miss_shapes = {}", self.accessibility.miss_shapes).unwrap();
+        writeln!(contents, "ball_size = {}", self.accessibility.ball_size).unwrap();
+        save_versioned(&mut *self.storage, Category::Config, "settings", SETTINGS_VERSION, &contents)
+    }
+
+    /// Update `current_theme` after the theme or high contrast has changed.
+    fn update_theme(&mut self) {
+        self.current_theme = match self.accessibility.high_contrast {
+            true => self.themes[self.theme].high_contrast(),
+            false => self.themes[self.theme].clone(),
+        };
+    }
+
+    /// Use new tuning values, and move things that end up outside the arena.
+    fn retune(&mut self,  tuning: Tuning) {
+        self.loaded_tuning = tuning;
+        let mut tuning = tuning;
+        tuning.ball_radius *= self.accessibility.ball_size;
+        self.tuning = tuning;
+        tuning.place_camera(&mut self.camera);
+        self.aim(self.player_target);
+        // the rackets move to their targets by themselves
+        let Tuning { arena, ball_radius, .. } = tuning;
+        for (pos, size) in self.ball_pos.iter_mut().zip(arena).take(2) {
+            *pos = clamp(*pos, (ball_radius, size-ball_radius));
+        }
+    }
+
+    /// Add the rally to the statistics, and end the match if it's over.
+    fn rally_over(&mut self,  missed_by: Side) {
+        self.rally.missed_by = missed_by;
+        self.stats.rallies.push(self.rally);
+        // whoever missed serves next
+        self.rally = Rally::serve(missed_by);
+        let match_misses = self.tuning.match_misses;
+        if self.player_misses >= match_misses  ||  self.opponent_misses >= match_misses {
+            self.finish_match();
+        }
+    }
+
+    /// Save the match that just ended, and show its summary and then the records before the next one.
+    fn finish_match(&mut self) {
+        let mut stats = mem::take(&mut self.stats);
+        stats.player = self.player.clone();
+        self.stats_log.push(stats);
+        if self.can_save_stats {
+            if let Err(e) = save_stats(&mut *self.storage, &self.stats_log) {
+                eprintln!("Cannot save match statistics: {}", e);
+            }
+        }
+        self.player_misses = 0;
+        self.opponent_misses = 0;
+        self.rally = Rally::serve(Side::Player);
+        self.ball_vel = [0.0, 0.0, self.tuning.ball_start_zspeed];
+        self.state = State::Summary;
+    }
 
     fn opponent(&mut self) {
+        let Tuning { arena, ball_radius, racket_size, .. } = self.tuning;
         if self.state == State::OpponentStart {
-            self.opponent_target = [ARENA[0]/2.0, ARENA[1]/2.0];
+            self.opponent_target = [arena[0]/2.0, arena[1]/2.0];
             self.state = State::Playing;
             return;
         }
 
         // predict where ball will end up without walls, and do nothing if not within reach
         if self.ball_vel[2] <= 0.0 {// moving away
-            //self.opponent_target = [ARENA[0]/2.0, ARENA[1]/2.0];
+            //self.opponent_target = [arena[0]/2.0, arena[1]/2.0];
             return
         }
-        let dist = ARENA[2]-BALL_RADIUS-self.ball_pos[2];
+        let dist = arena[2]-ball_radius-self.ball_pos[2];
         let time = dist / self.ball_vel[2];
         let moves = [self.ball_vel[0]*time, self.ball_vel[1]*time, dist];
-        let ends = [self.ball_pos[0]+moves[0], self.ball_pos[1]+moves[1], ARENA[2]-BALL_RADIUS];
-        if ends[0] < BALL_RADIUS || ends[0] > ARENA[0]-BALL_RADIUS
-        || ends[1] < BALL_RADIUS || ends[1] > ARENA[1]-BALL_RADIUS {
-            //self.opponent_target = [ARENA[0]/2.0, ARENA[1]/2.0];
+        let ends = [self.ball_pos[0]+moves[0], self.ball_pos[1]+moves[1], arena[2]-ball_radius];
+        if ends[0] < ball_radius || ends[0] > arena[0]-ball_radius
+        || ends[1] < ball_radius || ends[1] > arena[1]-ball_radius {
+            //self.opponent_target = [arena[0]/2.0, arena[1]/2.0];
             return
         }
-        let target_x = clamp(ends[0], (RACKET_SIZE[0]/2.0, ARENA[0]-RACKET_SIZE[0]/2.0));
-        let target_y = clamp(ends[1], (RACKET_SIZE[1]/2.0, ARENA[1]-RACKET_SIZE[1]/2.0));
+        let target_x = clamp(ends[0], (racket_size[0]/2.0, arena[0]-racket_size[0]/2.0));
+        let target_y = clamp(ends[1], (racket_size[1]/2.0, arena[1]-racket_size[1]/2.0));
         self.opponent_target = [target_x, target_y];
     }
 
+    /// Set where the player racket should move to, keeping it inside the arena.
+    fn aim(&mut self,  target: [f32; 2]) {
+        let Tuning { arena, racket_size, .. } = self.tuning;
+        let movable_x = (racket_size[0]/2.0, arena[0]-racket_size[0]/2.0);
+        let movable_y = (racket_size[1]/2.0, arena[1]-racket_size[1]/2.0);
+        self.player_target = [clamp(target[0], movable_x), clamp(target[1], movable_y)];
+    }
+
     fn start_pause(&mut self) {
         if self.state == State::Paused  ||  self.state == State::PlayerStart {
             self.state = State::Playing;
@@ -111,215 +606,530 @@ impl SpaceTennis {
             self.state = State::Paused;
         }
     }
+
+    fn action_start(&mut self,  action: Action) {
+        match action {
+            Action::MoveUp => self.steer(|keys| keys.up = true),
+            Action::MoveDown => self.steer(|keys| keys.down = true),
+            Action::MoveLeft => self.steer(|keys| keys.left = true),
+            Action::MoveRight => self.steer(|keys| keys.right = true),
+            Action::Serve => self.start_pause(),
+            Action::Pause => {
+                // starting with escape feels weird
+                self.state = match self.state {
+                    State::Playing => State::Paused,
+                    State::Paused => State::Playing,
+                    other => other
+                };
+            },
+            Action::Settings => {
+                self.settings = SettingsMenu {
+                    previous: self.state,
+                    selected: 0,
+                    rebinding: false,
+                    editing_name: false,
+                };
+                self.state = State::Settings;
+            },
+            Action::Debug => self.debug = !self.debug,
+        }
+    }
+
+    fn action_end(&mut self,  action: Action) {
+        match action {
+            Action::MoveUp => self.keys.up = false,
+            Action::MoveDown => self.keys.down = false,
+            Action::MoveLeft => self.keys.left = false,
+            Action::MoveRight => self.keys.right = false,
+            _ => {}
+        }
+    }
+
+    fn steer(&mut self,  press: fn(&mut Keys)) {
+        press(&mut self.keys);
+        if self.control == Control::Pointer {
+            self.control = Control::Keyboard;
+            self.keyboard_speed = [0.0, 0.0];
+        }
+    }
+
+    /// Move the target with the velocity of keyboard control.
+    fn steer_target(&mut self,  dt: f32) {
+        let direction = self.keys.direction();
+        let max_speed = self.tuning.player_max_speed;
+        for axis in 0..2 {
+            self.keyboard_speed[axis] = accelerate(
+                    self.keyboard_speed[axis],
+                    direction[axis],
+                    max_speed[axis],
+                    dt,
+                    &self.tuning,
+            );
+        }
+        let target = [
+            self.player_pos[0] + self.keyboard_speed[0]*dt,
+            self.player_pos[1] + self.keyboard_speed[1]*dt,
+        ];
+        self.aim(target);
+        for (axis, &wanted) in target.iter().enumerate() {
+            // stop at the walls instead of pushing against them
+            if self.player_target[axis] != wanted {
+                self.keyboard_speed[axis] = 0.0;
+            }
+        }
+    }
+
+    /// Tab always opens the settings screen, whatever it's bound to,
+    /// so that the player can always get back in to fix the bindings.
+    fn input_press(&mut self,  input: Input) {
+        if self.state == State::Settings {
+            self.settings_input(input);
+        } else if self.state == State::Records  ||  self.state == State::Summary {
+            self.screen_input(input);
+        } else if input == Input::Key(Key::Tab) {
+            self.action_start(Action::Settings);
+        } else if let Some(action) = self.bindings.action(input) {
+            self.action_start(action);
+        }
+    }
+
+    fn input_release(&mut self,  input: Input) {
+        if let Some(action) = self.bindings.action(input) {
+            self.action_end(action);
+        }
+    }
+
+    /// Navigate the settings screen.
+    ///
+    /// Raw keys are used in addition to the bound actions,
+    /// so that bad bindings cannot lock the player out.
+    fn settings_input(&mut self,  input: Input) {
+        if self.settings.editing_name {
+            self.name_input(input);
+            return;
+        }
+        let selected = SettingsRow::at(self.settings.selected);
+        if let (true, SettingsRow::Binding(selected)) = (self.settings.rebinding, selected) {
+            // Tab is reserved for opening the settings
+            if input != Input::Key(Key::Escape)  &&  input != Input::Key(Key::Tab) {
+                self.bindings.bind(input, selected);
+            }
+            self.settings.rebinding = false;
+            return;
+        }
+        match (input, self.bindings.action(input)) {
+            (Input::Key(Key::ArrowUp), _) | (_, Some(Action::MoveUp)) => {
+                self.settings.selected = (self.settings.selected + SETTINGS_ROWS - 1) % SETTINGS_ROWS;
+            }
+            (Input::Key(Key::ArrowDown), _) | (_, Some(Action::MoveDown)) => {
+                self.settings.selected = (self.settings.selected + 1) % SETTINGS_ROWS;
+            }
+            (Input::Key(Key::Enter), _) | (Input::Mouse(MouseButton::Left), _) => match selected {
+                SettingsRow::Binding(_) => self.settings.rebinding = true,
+                SettingsRow::Player => self.settings.editing_name = true,
+                SettingsRow::Records => self.open_records(State::Settings),
+                SettingsRow::Language => self.strings.next_locale(),
+                SettingsRow::Theme => {
+                    self.theme = (self.theme + 1) % self.themes.len();
+                    self.update_theme();
+                }
+                SettingsRow::HighContrast => {
+                    self.accessibility.high_contrast = !self.accessibility.high_contrast;
+                    self.update_theme();
+                }
+                SettingsRow::MissShapes => self.accessibility.miss_shapes = !self.accessibility.miss_shapes,
+                SettingsRow::BallSize => {
+                    let current = BALL_SIZES.iter().position(|&size| size == self.accessibility.ball_size);
+                    let next = current.map_or(0, |current| (current + 1) % BALL_SIZES.len());
+                    self.accessibility.ball_size = BALL_SIZES[next];
+                    self.retune(self.loaded_tuning);
+                }
+            },
+            (Input::Key(Key::Backspace), _) => {
+                if let SettingsRow::Binding(selected) = selected {
+                    self.bindings.clear(selected);
+                }
+            }
+            (Input::Key(Key::Escape), _) | (Input::Key(Key::Tab), _) | (_, Some(Action::Settings)) => {
+                if let Err(e) = self.bindings.save(&mut *self.storage) {
+                    eprintln!("Cannot save key bindings: {}", e);
+                }
+                if let Err(e) = self.save_settings() {
+                    eprintln!("Cannot save settings: {}", e);
+                }
+                self.state = match self.settings.previous {
+                    State::Playing => State::Paused,
+                    other => other,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Type the player name.
+    fn name_input(&mut self,  input: Input) {
+        let room = self.player.len() < NAME_LENGTH;
+        match input {
+            Input::Key(Key::Letter(c)) if room => self.player.push(c),
+            Input::Key(Key::Space) if room => self.player.push(' '),
+            Input::Key(Key::Backspace) => {
+                self.player.pop();
+            }
+            Input::Key(Key::Enter) | Input::Key(Key::Escape) | Input::Mouse(MouseButton::Left) => {
+                self.player = valid_player_name(&self.player).unwrap_or_else(default_player_name);
+                self.settings.editing_name = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn draw_settings(&self,  gfx: &mut Graphics) {
+        let strings = &self.strings;
+        let theme = &self.current_theme;
+        let text_color = theme[Paint::SettingsText];
+        let selected_color = theme[Paint::SettingsSelected];
+        let panel = [0.05, 0.05, 0.9, 0.9];
+        gfx.rounded_rectangle(theme[Paint::SettingsBackground], panel, 0.03);
+        gfx.stroke(text_color, 0.004, Outline::RoundedRectangle{ area: panel,  radius: 0.03 });
+        let title = strings.text("controls.title");
+        gfx.text(text_color, [0.5, 0.1], (Align::Center, VAlign::Center), 0.06, title);
+        // shrink the rows if there are too many to fit
+        let row_height = f32::min(0.07, 0.65/SETTINGS_ROWS as f32);
+        let (label_size, inputs_size) = (row_height*4.0/7.0, row_height*3.0/7.0);
+        let labels = (0..SETTINGS_ROWS)
+            .map(|i| match SettingsRow::at(i) {
+                SettingsRow::Binding(action) => strings.text(action_key(action)),
+                SettingsRow::Player => strings.text("controls.player"),
+                SettingsRow::Records => strings.text("controls.records"),
+                SettingsRow::Language => strings.text("controls.language"),
+                SettingsRow::Theme => strings.text("controls.theme"),
+                SettingsRow::HighContrast => strings.text("controls.high_contrast"),
+                SettingsRow::MissShapes => strings.text("controls.miss_shapes"),
+                SettingsRow::BallSize => strings.text("controls.ball_size"),
+            })
+            .collect::<Vec<_>>();
+        // put the inputs right after the longest label
+        let label_width = labels.iter()
+            .map(|label| gfx.measure_text(label, label_size).width)
+            .fold(0.0, f32::max);
+        let inputs_x = 0.1 + label_width + 0.03;
+        for (i, label) in labels.into_iter().enumerate() {
+            let y = 0.2 + row_height*i as f32;
+            let color = if i == self.settings.selected {selected_color} else {text_color};
+            gfx.text(color, [0.1, y], (Align::Left, VAlign::Center), label_size, label);
+            let inputs = match SettingsRow::at(i) {
+                SettingsRow::Binding(_) if i == self.settings.selected && self.settings.rebinding => {
+                    strings.text("controls.waiting").into_owned()
+                }
+                SettingsRow::Binding(action) => {
+                    self.bindings.inputs(action)
+                        .map(|input| input_label(input, strings))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+                SettingsRow::Player if self.settings.editing_name => format!("{}_", self.player),
+                SettingsRow::Player => self.player.clone(),
+                SettingsRow::Records => strings.text("controls.records.show").into_owned(),
+                SettingsRow::Language => strings.text("language").into_owned(),
+                SettingsRow::Theme => strings.text(&format!("theme.{}", theme.name)).into_owned(),
+                SettingsRow::HighContrast => strings.text(on_off(self.accessibility.high_contrast)).into_owned(),
+                SettingsRow::MissShapes => strings.text(on_off(self.accessibility.miss_shapes)).into_owned(),
+                SettingsRow::BallSize => {
+                    let percent = (self.accessibility.ball_size*100.0).round();
+                    strings.format("controls.ball_size.value", &[("percent", &percent)])
+                }
+            };
+            // shrink lists of inputs that would go outside the menu
+            let available = panel[0] + panel[2] - 0.02 - inputs_x;
+            let width = gfx.measure_text(&inputs, inputs_size).width;
+            let size = if width > available {inputs_size*available/width} else {inputs_size};
+            gfx.text(color, [inputs_x, y], (Align::Left, VAlign::Center), size, inputs);
+        }
+        let help = match SettingsRow::at(self.settings.selected) {
+            _ if self.settings.rebinding => "controls.help.rebinding",
+            _ if self.settings.editing_name => "controls.help.name",
+            SettingsRow::Binding(_) => "controls.help",
+            _ => "controls.help.option",
+        };
+        gfx.text(text_color, [0.5, 0.9], (Align::Center, VAlign::Center), 0.03, strings.text(help));
+    }
+
 }
 
 impl Game for SpaceTennis {
     fn render(&mut self,  gfx: &mut Graphics) {
-        /*
-        at the center of the window there is a view cone with a certain angle (field of view)
-        at x dept the distance from top to bottom or left to right of view
-        is 2*x*tan(fov/2). (hosliggende er 1, ikke hypotenusen)
-        for now the aspect ratio is assumed to be 1:1, so horizontal and vertical FOV is equal.
-        */
-        // step 0: find view distance that satisfies FRONT_FILLS:
-        // at x distance the viewable area*FRONT_FILLS is ARENA[0] =>
-        // 2*view_distance*tan(fov/2)*FRONT_FILLS=ARENA[0]
-        let view_distance = ARENA[0]/(2.0*FRONT_FILLS*f32::tan(FOV/2.0));
-
-        // order depends on its z position
-        fn draw_ball(ball_pos_game: [f32;3],  view_distance: f32,  gfx: &mut Graphics) {
-            let ball_color = hex(BALL_COLOR);
-            let ball_viewable = 2.0*(view_distance+ball_pos_game[2])*f32::tan(FOV/2.0);
-            let ball_depth_frac = (ARENA[0]/ball_viewable, ARENA[1]/ball_viewable);
-            let ball_offset = (0.5 - ball_depth_frac.0/2.0,  0.5 - ball_depth_frac.1/2.0);
-            let ball_pos_screen = [
-                ball_offset.0 + ball_depth_frac.0*ball_pos_game[0],
-                ball_offset.1 + ball_depth_frac.1*ball_pos_game[1]
-            ];
-            let ball_frac = BALL_RADIUS / ball_viewable;
-            gfx.circle(ball_color, ball_pos_screen, ball_frac);
-        }
-        if self.ball_pos[2] > ARENA[2] {
-            draw_ball(self.ball_pos, view_distance, gfx);
-        }
-
-        fn draw_wall_marker(color: &str,  depth: f32,  width: f32,  gfx: &mut Graphics) {
+        let camera = &self.camera;
+        let tuning = &self.tuning;
+        let theme = &self.current_theme;
+        let Tuning { arena, ball_radius, miss_ball_radius, racket_size, wall_lines, line_width, .. } = *tuning;
+        let line_width_edge = tuning.line_width_edge();
+        gfx.set_visible(Layer::Debug, self.debug);
+
+        // cover the whole window without stretching
+        let visible = camera.visible_area();
+        let side = f32::max(visible[2], visible[3]);
+        gfx.rectangle(theme[Paint::Background], visible);
+        gfx.image(theme[Paint::Stars], BACKGROUND.sprite(), [0.5-side/2.0, 0.5-side/2.0, side, side]);
+
+        // keep the ball from being drawn outside the arena when it flies past the player
+        let clip_topleft = camera.project([0.0, 0.0, -line_width_edge/2.0]);
+        let clip_bottomright = camera.project([arena[0], arena[1], -line_width_edge/2.0]);
+        gfx.push_clip([
+            clip_topleft[0], clip_topleft[1],
+            clip_bottomright[0]-clip_topleft[0], clip_bottomright[1]-clip_topleft[1],
+        ]);
+
+        /// A band around the inside of the arena walls,
+        /// with `colors` for the near and far edge.
+        fn draw_wall_band(colors: [Color; 2],  z: f32,  width: f32,  tuning: &Tuning,  gfx: &mut Graphics) {
             // width is on the wall, aka the z-dimension.
-            // find the draw width by calculating the rectangle of the near and
-            // far edge, and setting the center of the lines to the median.
-            let color = hex(color);
-            let near_viewable = 2.0*(depth-width/2.0)*f32::tan(FOV/2.0);
-            let far_viewable = 2.0*(depth+width/2.0)*f32::tan(FOV/2.0);
-            let near_area_frac = (ARENA[0]/near_viewable, ARENA[1]/near_viewable);
-            let far_area_frac = (ARENA[0]/far_viewable, ARENA[1]/far_viewable);
-            let near_topleft = (0.5 - (near_area_frac.0/2.0),  0.5 - (near_area_frac.1/2.0));
-            let near_bottomright = (0.5 + (near_area_frac.0/2.0),  0.5 + (near_area_frac.1/2.0));
-            let far_topleft = (0.5 - (far_area_frac.0/2.0),  0.5 - (far_area_frac.1/2.0));
-            let radius = ((far_topleft.0-near_topleft.0)/2.0, (far_topleft.1-near_topleft.1)/2.0);
-            let offset_x = (near_topleft.0+radius.0, near_bottomright.0-radius.0);
-            let offset_y = (near_topleft.1+radius.1, near_bottomright.1-radius.1);
-            // draw corners completely, and only once in case the color is translucent
-            let top    = [offset_x.0-radius.0, offset_y.0, offset_x.1-radius.0, offset_y.0];
-            let bottom = [offset_x.0+radius.0, offset_y.1, offset_x.1+radius.0, offset_y.1];
-            let left   = [offset_x.0, offset_y.0+radius.1, offset_x.0, offset_y.1+radius.1];
-            let right  = [offset_x.1, offset_y.0-radius.1, offset_x.1, offset_y.1-radius.1];
-            gfx.line(color, radius.1, top);
-            gfx.line(color, radius.1, bottom);
-            gfx.line(color, radius.0, left);
-            gfx.line(color, radius.0, right);
-        }
-        // draw the walls themselves
-        draw_wall_marker(WALL_COLOR, view_distance+ARENA[2]/2.0, ARENA[2], gfx);
-        let interval = ARENA[2]/(WALL_LINES+1) as f32;
+            let [near_color, far_color] = colors;
+            let (near, far) = (z-width/2.0, z+width/2.0);
+            let arena = tuning.arena;
+            let corners = [[0.0, 0.0], [arena[0], 0.0], [arena[0], arena[1]], [0.0, arena[1]]];
+            for (i, &start) in corners.iter().enumerate() {
+                let end = corners[(i+1) % corners.len()];
+                gfx.shaded_quad([near_color, near_color, far_color, far_color], [
+                    [start[0], start[1], near],
+                    [end[0], end[1], near],
+                    [end[0], end[1], far],
+                    [start[0], start[1], far],
+                ]);
+            }
+        }
+        // draw the walls themselves, fading into the distance,
+        // and extended under the edge markers so that those are sorted after them
+        draw_wall_band([theme[Paint::Wall], theme[Paint::WallFar]], arena[2]/2.0, arena[2]+line_width_edge, tuning, gfx);
+        let interval = arena[2]/(wall_lines+1) as f32;
         // the markers on the edges are thicker
-        draw_wall_marker(WALL_LINE_COLOR, view_distance, LINE_WIDTH_EDGE, gfx);
-        for n in 1..(WALL_LINES+1) {
-            draw_wall_marker(WALL_LINE_COLOR, view_distance + interval*n as f32, LINE_WIDTH, gfx);
-        }
-        draw_wall_marker(WALL_LINE_COLOR, view_distance+ARENA[2], LINE_WIDTH_EDGE, gfx);
-
-        fn draw_racket(pos: [f32;2]/*in arena*/, depth: f32/*from view*/, gfx: &mut Graphics) {
-            let fill_color = hex(RACKET_COLOR);
-            let border_color = hex(RACKET_BORDER_COLOR);
-            let viewable = 2.0*depth*f32::tan(FOV/2.0);
-            let area_frac = [ARENA[0]/viewable, ARENA[1]/viewable];
-            let border_frac = [RACKET_BORDER_WIDTH[0]/viewable, RACKET_BORDER_WIDTH[1]/viewable];
-            let area_offset = [0.5-area_frac[0]/2.0, 0.5-area_frac[1]/2.0];
-            let racket_frac = [RACKET_SIZE[0]/viewable, RACKET_SIZE[1]/viewable];
-            let racket_pos = [area_offset[0]+area_frac[0]*pos[0], area_offset[1]+area_frac[1]*pos[1]];
-            let fill_area = [
-                racket_pos[0]-racket_frac[0]/2.0+border_frac[0],
-                racket_pos[1]-racket_frac[1]/2.0+border_frac[1],
-                racket_frac[0]-2.0*border_frac[0],
-                racket_frac[1]-2.0*border_frac[1],
-            ];
-            gfx.rectangle(fill_color, fill_area);
-            let radius = [border_frac[0]/2.0, border_frac[1]/2.0]; // [left/right, top/bottom]
-            let (left,top,right,bottom) = (
-                racket_pos[0]-racket_frac[0]/2.0+radius[0],
-                racket_pos[1]-racket_frac[1]/2.0+radius[1],
-                racket_pos[0]+racket_frac[0]/2.0-radius[0],
-                racket_pos[1]+racket_frac[1]/2.0-radius[1],
-            );
-            gfx.line(border_color, radius[1], [left-radius[0], top, right-radius[0], top]);
-            gfx.line(border_color, radius[0], [right, top-radius[1], right, bottom-radius[1]]);
-            gfx.line(border_color, radius[1], [left+radius[0], bottom, right+radius[0], bottom]);
-            gfx.line(border_color, radius[0], [left, top+radius[1], left, bottom+radius[1]]);
+        draw_wall_band([theme[Paint::WallLine]; 2], 0.0, line_width_edge, tuning, gfx);
+        for n in 1..(wall_lines+1) {
+            draw_wall_band([theme[Paint::WallLine]; 2], interval*n as f32, line_width, tuning, gfx);
+        }
+        draw_wall_band([theme[Paint::WallLine]; 2], arena[2], line_width_edge, tuning, gfx);
+
+        fn draw_racket(pos: [f32;2]/*in arena*/, z: f32, tuning: &Tuning, theme: &Theme, gfx: &mut Graphics) {
+            fn rectangle(color: Color,  [left, top, right, bottom]: [f32; 4],  z: f32,  gfx: &mut Graphics) {
+                gfx.quad(color, [[left, top, z], [right, top, z], [right, bottom, z], [left, bottom, z]]);
+            }
+            let fill_color = theme[Paint::Racket];
+            let border_color = theme[Paint::RacketBorder];
+            let half = [tuning.racket_size[0]/2.0, tuning.racket_size[1]/2.0];
+            let border = tuning.racket_border_width; // [left/right, top/bottom]
+            let (left, top) = (pos[0]-half[0], pos[1]-half[1]);
+            let (right, bottom) = (pos[0]+half[0], pos[1]+half[1]);
+            let (inner_left, inner_top) = (left+border[0], top+border[1]);
+            let (inner_right, inner_bottom) = (right-border[0], bottom-border[1]);
+            rectangle(fill_color, [inner_left, inner_top, inner_right, inner_bottom], z, gfx);
+            rectangle(border_color, [left, top, right, inner_top], z, gfx);
+            rectangle(border_color, [left, inner_bottom, right, bottom], z, gfx);
+            rectangle(border_color, [left, inner_top, inner_left, inner_bottom], z, gfx);
+            rectangle(border_color, [inner_right, inner_top, right, inner_bottom], z, gfx);
         }
-        // opponent racket
-        draw_racket(self.opponent_pos, view_distance+ARENA[2], gfx);
+        draw_racket(self.opponent_pos, arena[2], tuning, theme, gfx);
+        draw_racket(self.player_pos, 0.0, tuning, theme, gfx);
 
-        // step 5: ball inside arena
-        if self.ball_pos[2] <= ARENA[2]  &&  self.ball_pos[2] >= 0.0 {
-            draw_wall_marker(BALL_LINE_COLOR, view_distance+self.ball_pos[2], LINE_WIDTH, gfx);
-            draw_ball(self.ball_pos, view_distance, gfx);
+        if self.ball_pos[2] <= arena[2]  &&  self.ball_pos[2] >= 0.0 {
+            draw_wall_band([theme[Paint::BallLine]; 2], self.ball_pos[2], line_width, tuning, gfx);
         }
+        gfx.billboard(theme[Paint::Ball], BALL_SPRITE, self.ball_pos, [ball_radius*2.0; 2]);
 
-        // player racket
-        draw_racket(self.player_pos, view_distance, gfx);
+        gfx.draw_world(camera);
+        gfx.pop_clip();
+        gfx.set_layer(Layer::Hud);
 
         // misses
-        let miss_color = hex(MISS_COLOR);
-        let front_viewable = 2.0*view_distance*f32::tan(FOV/2.0);
-        let radius_frac = MISS_BALL_RADIUS/front_viewable;
+        /// A miss lit from the top left.
+        fn draw_miss(center: [f32; 2],  radius: f32,  shape: MissShape,  theme: &Theme,  gfx: &mut Graphics) {
+            let gradient = Gradient::Radial {
+                center: [center[0]-radius/3.0, center[1]-radius/3.0],
+                radius: radius*4.0/3.0,
+                colors: [theme[Paint::MissHighlight], theme[Paint::Miss]],
+            };
+            match shape {
+                MissShape::Ball => {
+                    let area = [center[0]-radius, center[1]-radius, radius*2.0, radius*2.0];
+                    gfx.gradient(gradient, Outline::Ellipse{ area }.points(0.0));
+                }
+                MissShape::Cross => {
+                    // two diagonal bars
+                    let (along, across) = (radius*0.5f32.sqrt(), radius*0.2);
+                    for direction in [1.0, -1.0] {
+                        gfx.gradient(gradient, [
+                            [center[0]-along-across*direction, center[1]-along*direction+across],
+                            [center[0]-along+across*direction, center[1]-along*direction-across],
+                            [center[0]+along+across*direction, center[1]+along*direction-across],
+                            [center[0]+along-across*direction, center[1]+along*direction+across],
+                        ]);
+                    }
+                }
+                MissShape::Diamond => {
+                    gfx.gradient(gradient, [
+                        [center[0], center[1]-radius],
+                        [center[0]+radius, center[1]],
+                        [center[0], center[1]+radius],
+                        [center[0]-radius, center[1]],
+                    ]);
+                }
+            }
+        }
+        let front_topleft = camera.project([0.0, 0.0, 0.0]);
+        let front_bottomright = camera.project([arena[0], arena[1], 0.0]);
+        let radius_frac = camera.project_size(miss_ball_radius, camera.depth([0.0, 0.0, 0.0]));
         let n_offset = 3.0*radius_frac;
-        let start_y = 0.5-(ARENA[1]/front_viewable)/2.0;
-        let player_x = 0.5 + (ARENA[0]/front_viewable)/2.0 + 2.0*radius_frac;
-        let opponent_x = 0.5 - (ARENA[0]/front_viewable)/2.0 - 4.0*radius_frac;
+        let start_y = front_topleft[1];
+        let player_x = front_bottomright[0] + 2.0*radius_frac;
+        let opponent_x = front_topleft[0] - 4.0*radius_frac;
         if self.player_misses > 0  || self.opponent_misses > 0{
             gfx.text(
-                    miss_color,
+                    theme[Paint::Miss],
                     [opponent_x, start_y - n_offset],
-                    [Align::Left, Align::Left],
+                    (Align::Left, VAlign::Top),
                     radius_frac*2.0,
-                    "opponents misses",
+                    self.strings.plural("misses.opponent", self.opponent_misses.into(), &[]),
             );
             gfx.text(
-                    miss_color,
+                    theme[Paint::Miss],
                     [player_x + radius_frac*2.0, start_y - n_offset],
-                    [Align::Right, Align::Left],
+                    (Align::Right, VAlign::Top),
                     radius_frac*2.0,
-                    "your misses",
+                    self.strings.plural("misses.player", self.player_misses.into(), &[]),
             );
         }
-        for n in (0..self.player_misses).take(MAX_MISSES as usize) {
-            let x = player_x + radius_frac;
-            let y = start_y + (n_offset*n as f32) + radius_frac;
-            gfx.circle(miss_color, [x, y], radius_frac);
-        }
-        if self.player_misses > MAX_MISSES {
-            let top = start_y + n_offset*(MAX_MISSES as f32);
-            let vertical = [player_x+radius_frac*2.0/3.0, top, radius_frac*2.0/3.0, radius_frac*2.0];
-            let horizontal = [player_x, top+radius_frac*2.0/3.0, radius_frac*2.0, radius_frac*2.0/3.0];
-            gfx.rectangle(miss_color, vertical);
-            gfx.rectangle(miss_color, horizontal);
-        }
-        for n in (0..self.opponent_misses).take(MAX_MISSES as usize) {
-            let x = opponent_x + radius_frac;
-            let y = start_y + (n_offset*n as f32) + radius_frac;
-            gfx.circle(miss_color, [x, y], radius_frac);
-        }
-        if self.opponent_misses > MAX_MISSES {
-            let top = start_y + n_offset*MAX_MISSES as f32;
-            let vertical = [opponent_x+radius_frac*2.0/3.0, top, radius_frac*2.0/3.0, radius_frac*2.0];
-            let horizontal = [opponent_x, top+radius_frac*2.0/3.0, radius_frac*2.0, radius_frac*2.0/3.0];
-            gfx.rectangle(miss_color, vertical);
-            gfx.rectangle(miss_color, horizontal);
+        /// A column of misses, in units of their radius,
+        /// and a plus sign below them if there are too many.
+        fn draw_misses(misses: u32,  shape: MissShape,  tuning: &Tuning,  theme: &Theme,  gfx: &mut Graphics) {
+            let max_misses = tuning.max_misses();
+            for n in 0..misses.min(max_misses) {
+                draw_miss([1.0, 3.0*n as f32 + 1.0], 1.0, shape, theme, gfx);
+            }
+            if misses > max_misses {
+                let top = 3.0*max_misses as f32;
+                let miss_color = theme[Paint::Miss];
+                gfx.rectangle(miss_color, [2.0/3.0, top, 2.0/3.0, 2.0]);
+                gfx.rectangle(miss_color, [0.0, top+2.0/3.0, 2.0, 2.0/3.0]);
+            }
         }
-
-        if self.ball_pos[2] < 0.0 {
-            draw_ball(self.ball_pos, view_distance, gfx);
+        let (player_shape, opponent_shape) = match self.accessibility.miss_shapes {
+            true => (MissShape::Cross, MissShape::Diamond),
+            false => (MissShape::Ball, MissShape::Ball),
+        };
+        for (misses, shape, x) in [
+            (self.player_misses, player_shape, player_x),
+            (self.opponent_misses, opponent_shape, opponent_x),
+        ] {
+            gfx.push_translate([x, start_y]);
+            gfx.push_scale([radius_frac, radius_frac]);
+            draw_misses(misses, shape, tuning, theme, gfx);
+            gfx.pop_transform();
+            gfx.pop_transform();
         }
 
-        // UI
-        let arena_starts = [
-            (1.0-ARENA[0]/front_viewable) / 2.0,
-            (1.0-ARENA[1]/front_viewable) / 2.0,
-        ];
+        // UI, placed between the arena and the edges of the window
+        let margin = (front_topleft[1]-visible[1])*0.6;
+        let (top_y, bottom_y) = (visible[1]+margin, visible[1]+visible[3]-margin);
         gfx.text(
-                hex(BALL_COLOR),
-                [0.35, arena_starts[1]*0.6],
-                [Align::Center, Align::Center],
+                theme[Paint::Text],
+                [0.35, top_y],
+                (Align::Center, VAlign::Center),
                 0.04,
-                format!("set {}", 1+self.player_misses+self.opponent_misses),
+                self.strings.format("set", &[("number", &(1+self.player_misses+self.opponent_misses))]),
         );
         let speed = self.ball_vel[0].hypot(self.ball_vel[1]).hypot(self.ball_vel[2]);
+        let speed_text = self.strings.format("speed", &[("speed", &speed)]);
+        let speed_width = gfx.measure_text(&speed_text, 0.04).width;
+        // spins faster the faster the ball moves
+        gfx.push_translate([0.65 - speed_width/2.0 - SPIN_INDICATOR_SIZE, top_y]);
+        gfx.push_rotate(self.ball_spin);
+        let half = SPIN_INDICATOR_SIZE/2.0;
+        gfx.image(theme[Paint::Ball], BALL_SPRITE, [-half, -half, SPIN_INDICATOR_SIZE, SPIN_INDICATOR_SIZE]);
+        gfx.pop_transform();
+        gfx.pop_transform();
         gfx.text(
-                hex(BALL_COLOR),
-                [0.65, arena_starts[1]*0.6],
-                [Align::Center, Align::Center],
+                theme[Paint::Text],
+                [0.65, top_y],
+                (Align::Center, VAlign::Center),
                 0.04,
-                format!("speed: {:.2}", speed),
+                speed_text,
         );
 
+        gfx.set_layer(Layer::Overlay);
+        // wrapped on narrow windows
+        let message_box = TextBox::new(visible[2]-2.0*MESSAGE_MARGIN, Align::Center, VAlign::Center);
         if self.state == State::Paused {
             // draw pause sign
-            let pause_color = hex(PAUSE_COLOR);
-            gfx.rectangle(pause_color, [0.4, 0.4, 0.075, 0.2]);
-            gfx.rectangle(pause_color, [0.525, 0.4, 0.075, 0.2]);
-            gfx.text(
-                    pause_color,
-                    [0.5, 1.0 - arena_starts[1]*0.6],
-                    [Align::Center, Align::Center],
+            gfx.rectangle(theme[Paint::Pause], [0.4, 0.4, 0.075, 0.2]);
+            gfx.rectangle(theme[Paint::Pause], [0.525, 0.4, 0.075, 0.2]);
+            gfx.text_box(
+                    theme[Paint::Pause],
+                    [0.5, bottom_y],
+                    message_box,
                     0.05,
-                    "Paused, click any mouse button to continue",
+                    self.strings.text("paused"),
             );
         } else if self.state == State::PlayerStart {
-            gfx.text(
-                    hex(BALL_COLOR),
-                    [0.5, 1.0 - arena_starts[1]*0.6],
-                    [Align::Center, Align::Center],
+            if self.player_misses == 0  &&  self.opponent_misses == 0 {
+                let height = LOGO_WIDTH * LOGO_SIZE[1]/LOGO_SIZE[0];
+                let area = [0.5-LOGO_WIDTH/2.0, 0.3-height/2.0, LOGO_WIDTH, height];
+                gfx.image(theme[Paint::Logo], LOGO_SPRITE, area);
+            }
+            gfx.text_box(
+                    theme[Paint::Text],
+                    [0.5, bottom_y],
+                    message_box,
                     0.05,
-                    "Start by clicking any mouse button.",
+                    self.strings.text("start"),
             );
-    }
+        } else if self.state == State::Settings {
+            self.draw_settings(gfx);
+        } else if self.state == State::Records {
+            self.draw_records(gfx);
+        } else if let (State::Summary, Some(stats)) = (self.state, self.stats_log.last()) {
+            self.draw_summary(stats, gfx);
+        }
+
+        // where the ball is going and where the rackets are heading
+        gfx.set_layer(Layer::Debug);
+        let ball_ahead = [0, 1, 2].map(|axis| self.ball_pos[axis] + self.ball_vel[axis]*DEBUG_LOOKAHEAD);
+        gfx.line_3d(theme[Paint::Debug], line_width/4.0, [self.ball_pos, ball_ahead]);
+        for (target, z) in [(self.player_target, 0.0), (self.opponent_target, arena[2])] {
+            let size = racket_size[1]/4.0;
+            gfx.line_3d(theme[Paint::Debug], line_width/4.0, [
+                [target[0]-size, target[1], z],
+                [target[0]+size, target[1], z],
+            ]);
+            gfx.line_3d(theme[Paint::Debug], line_width/4.0, [
+                [target[0], target[1]-size, z],
+                [target[0], target[1]+size, z],
+            ]);
+        }
+        gfx.draw_world(camera);
+        gfx.text(
+                theme[Paint::Debug],
+                [visible[0]+0.01, visible[1]+visible[3]-0.01],
+                (Align::Left, VAlign::Bottom),
+                0.03,
+                format!(
+                    "ball {:.2?} velocity {:.2?}",
+                    self.ball_pos,
+                    self.ball_vel,
+                ),
+        );
     }
 
 
     fn update(&mut self,  dt: f32) {
-        if self.state == State::Paused {
+        self.touches.update(dt);
+        if let Some(index) = State::TIMED.iter().position(|&timed| timed == self.state) {
+            self.stats.seconds[index] += dt;
+        }
+        if self.state == State::Paused  ||  self.state == State::Settings
+        || self.state == State::Records  ||  self.state == State::Summary {
             return;
         }
+        let Tuning { arena, ball_radius, racket_size, player_max_speed, opponent_max_speed, .. } = self.tuning;
+        let Tuning { player_restart_delay, opponent_restart_delay, .. } = self.tuning;
+        let Tuning { ball_zspeed_level_add, bracket_speed_transfer, .. } = self.tuning;
 
         // move rackets: be kind to the players and do that first
         fn move_racket(racket: &mut[f32;2],  target: &[f32;2],  max_speed: [f32;2],  dt: f32) -> [f32;2] {
@@ -328,80 +1138,98 @@ impl Game for SpaceTennis {
             let move_x = clamp(diff[0], (-max_move[0], max_move[0]));
             let move_y = clamp(diff[1], (-max_move[1], max_move[1]));
             *racket = [racket[0]+move_x, racket[1]+move_y];
-            return [move_x/dt, move_y/dt];
+            [move_x/dt, move_y/dt]
         }
-        let player_speed = move_racket(&mut self.player_pos, &self.player_target, PLAYER_MAX_SPEED, dt);
-        let opponent_speed = move_racket(&mut self.opponent_pos, &self.opponent_target, OPPONENT_MAX_SPEED, dt);
+        if self.control == Control::Keyboard {
+            self.steer_target(dt);
+        }
+        let player_speed = move_racket(&mut self.player_pos, &self.player_target, player_max_speed, dt);
+        let opponent_speed = move_racket(&mut self.opponent_pos, &self.opponent_target, opponent_max_speed, dt);
 
         if self.state == State::PlayerStart {
-            self.ball_pos = [self.player_pos[0], self.player_pos[1], BALL_RADIUS];
+            self.ball_pos = [self.player_pos[0], self.player_pos[1], ball_radius];
             // no loss of speed because it was following the racket without delay
             self.ball_vel = [player_speed[0], player_speed[1], self.ball_vel[2]];
         } else if self.state == State::OpponentStart {
-            self.ball_pos = [self.opponent_pos[0], self.opponent_pos[1], ARENA[2]-BALL_RADIUS];
+            self.ball_pos = [self.opponent_pos[0], self.opponent_pos[1], arena[2]-ball_radius];
             self.ball_vel = [opponent_speed[0], opponent_speed[1], self.ball_vel[2]];
         } else if self.state == State::Playing {
+            let speed = self.ball_vel[0].hypot(self.ball_vel[1]).hypot(self.ball_vel[2]);
+            self.ball_spin = (self.ball_spin + speed*SPIN_PER_DISTANCE*dt) % (2.0*PI);
+            self.stats.max_speed = self.stats.max_speed.max(speed);
+            self.stats.ball_distance += speed*dt;
             // check boundaries and bounce / gameover
             let moved = [self.ball_vel[0]*dt, self.ball_vel[1]*dt, self.ball_vel[2]*dt];
             let mut pos = [self.ball_pos[0]+moved[0], self.ball_pos[1]+moved[1], self.ball_pos[2]+moved[2]];
             // check for score. allow the ball to leave the arena for a bit so that it doesn't
             // look like a bug
-            if pos[2] < -ARENA[2]*PLAYER_RESTART_DELAY {
+            if pos[2] < -arena[2]*player_restart_delay {
                 // game over, restart
                 self.player_misses += 1;
-                self.ball_pos = [self.player_pos[0], self.player_pos[1], BALL_RADIUS];
+                self.ball_pos = [self.player_pos[0], self.player_pos[1], ball_radius];
                 let z_speed = self.ball_vel[2];
-                self.ball_vel = [0.0, 0.0, -z_speed+BALL_ZSPEED_LEVEL_ADD];
+                self.ball_vel = [0.0, 0.0, -z_speed+ball_zspeed_level_add];
                 self.state = State::PlayerStart;
+                self.rally_over(Side::Player);
                 return;
-            } else if pos[2] > ARENA[2]*(1.0+OPPONENT_RESTART_DELAY) {
+            } else if pos[2] > arena[2]*(1.0+opponent_restart_delay) {
                 self.opponent_misses += 1;
-                self.ball_pos = [self.opponent_pos[0], self.opponent_pos[1], ARENA[2]-BALL_RADIUS];
+                self.ball_pos = [self.opponent_pos[0], self.opponent_pos[1], arena[2]-ball_radius];
                 let z_speed = self.ball_vel[2];
-                self.ball_vel = [0.0, 0.0, -z_speed-BALL_ZSPEED_LEVEL_ADD];
+                self.ball_vel = [0.0, 0.0, -z_speed-ball_zspeed_level_add];
                 self.state = State::OpponentStart;
+                self.rally_over(Side::Opponent);
                 return;
-            } else if pos[2] < 0.0  ||  pos[2] > ARENA[2] {
+            } else if pos[2] < 0.0  ||  pos[2] > arena[2] {
                 // update pos but don't do wall or racket interaction
                 self.ball_pos = pos;
                 self.opponent();
                 return;
             }
-            if pos[0] < BALL_RADIUS {
+            if pos[0] < ball_radius {
                 self.ball_vel[0] *= -1.0;
-                pos[0] = BALL_RADIUS+(BALL_RADIUS-pos[0]);
-            } else if pos[0] > ARENA[0]-BALL_RADIUS {
+                pos[0] = ball_radius+(ball_radius-pos[0]);
+                self.rally.wall_bounces += 1;
+            } else if pos[0] > arena[0]-ball_radius {
                 self.ball_vel[0] *= -1.0;
-                pos[0] = (ARENA[0]-BALL_RADIUS)-(pos[0]-(ARENA[0]-BALL_RADIUS));
+                pos[0] = (arena[0]-ball_radius)-(pos[0]-(arena[0]-ball_radius));
+                self.rally.wall_bounces += 1;
             }
-            if pos[1] < BALL_RADIUS {
+            if pos[1] < ball_radius {
                 self.ball_vel[1] *= -1.0;
-                pos[1] = BALL_RADIUS+(BALL_RADIUS-pos[1]);
-            } else if pos[1] > ARENA[1]-BALL_RADIUS {
-                // println!("wrong: {}", (pos[1]-(ARENA[1]-BALL_RADIUS)));
+                pos[1] = ball_radius+(ball_radius-pos[1]);
+                self.rally.wall_bounces += 1;
+            } else if pos[1] > arena[1]-ball_radius {
+                // println!("wrong: {}", (pos[1]-(arena[1]-ball_radius)));
                 // println!("old: {:?}, {:?}", self.ball_vel, self.ball_pos);
                 self.ball_vel[1] *= -1.0;
-                pos[1] = (ARENA[1]-BALL_RADIUS)-(pos[1]-(ARENA[1]-BALL_RADIUS));
+                pos[1] = (arena[1]-ball_radius)-(pos[1]-(arena[1]-ball_radius));
+                self.rally.wall_bounces += 1;
                 // println!("new: {:?}, {:?}", self.ball_vel, self.ball_pos);
             }
 
-            fn within(pos: [f32; 3], racket_center: [f32; 2]) -> bool {
-                f32::abs(pos[0] - racket_center[0]) <= RACKET_SIZE[0] &&
-                f32::abs(pos[1] - racket_center[1]) <= RACKET_SIZE[1]
-            }
-            if pos[2] < BALL_RADIUS && within(pos, self.player_pos) {
-                self.ball_vel[0] += player_speed[0]*BRACKET_SPEED_TRANSFER;
-                self.ball_vel[1] += player_speed[1]*BRACKET_SPEED_TRANSFER;
+            // a larger ball is also easier to hit
+            let enlarged = ball_radius - self.loaded_tuning.ball_radius;
+            let within = |pos: [f32; 3], racket_center: [f32; 2]| -> bool {
+                f32::abs(pos[0] - racket_center[0]) <= racket_size[0] + enlarged &&
+                f32::abs(pos[1] - racket_center[1]) <= racket_size[1] + enlarged
+            };
+            if pos[2] < ball_radius && within(pos, self.player_pos) {
+                self.ball_vel[0] += player_speed[0]*bracket_speed_transfer;
+                self.ball_vel[1] += player_speed[1]*bracket_speed_transfer;
                 self.ball_vel[2] *= -1.0;
-                pos[2] = BALL_RADIUS-(pos[2]-BALL_RADIUS);
-            } else if pos[2] > ARENA[2]-BALL_RADIUS && within(pos, self.opponent_pos) {
-                self.ball_vel[0] += opponent_speed[0]*BRACKET_SPEED_TRANSFER;
-                self.ball_vel[1] += opponent_speed[1]*BRACKET_SPEED_TRANSFER;
+                pos[2] = ball_radius-(pos[2]-ball_radius);
+                self.rally.hits[Side::Player as usize] += 1;
+            } else if pos[2] > arena[2]-ball_radius && within(pos, self.opponent_pos) {
+                self.ball_vel[0] += opponent_speed[0]*bracket_speed_transfer;
+                self.ball_vel[1] += opponent_speed[1]*bracket_speed_transfer;
                 self.ball_vel[2] *= -1.0;
-                pos[2] = (ARENA[2]-BALL_RADIUS)-(pos[2]-(ARENA[2]-BALL_RADIUS));
+                pos[2] = (arena[2]-ball_radius)-(pos[2]-(arena[2]-ball_radius));
+                self.rally.hits[Side::Opponent as usize] += 1;
             }
             self.ball_pos = pos;
-            if pos[0] > 0.9 || pos[0] < 0.1 || pos[1] > 0.9 || pos[1] < 0.1 {
+            let escaped = |axis: usize| pos[axis] < ball_radius*0.8 || pos[axis] > arena[axis]-ball_radius*0.8;
+            if escaped(0) || escaped(1) {
                 self.state = State::Paused;
                 println!("vel: {:?}, pos: {:?}", self.ball_vel, self.ball_pos);
             }
@@ -411,90 +1239,65 @@ impl Game for SpaceTennis {
         self.opponent();
     }
 
+    fn resize(&mut self,  size: [f32; 2]) {
+        self.camera.resize(size);
+    }
+
     fn mouse_move(&mut self,  pos: [f32; 2]) {
-        let view_distance = ARENA[0]/(2.0*FRONT_FILLS*f32::tan(FOV/2.0));
-        let front_viewable = 2.0*view_distance*f32::tan(FOV/2.0);
-        let front_frac = [ARENA[0]/front_viewable, ARENA[1]/front_viewable];
-        let front_offset = [0.5-front_frac[0]/2.0, 0.5-front_frac[1]/2.0];
-        let pos = [(pos[0]-front_offset[0])/front_frac[0], (pos[1]-front_offset[1])/front_frac[1]];
-        let movable_x = (RACKET_SIZE[0]/2.0, ARENA[0]-RACKET_SIZE[0]/2.0);
-        let movable_y = (RACKET_SIZE[1]/2.0, ARENA[1]-RACKET_SIZE[1]/2.0);
-        self.player_target = [clamp(pos[0], movable_x), clamp(pos[1], movable_y)];
+        self.control = Control::Pointer;
+        let pos = self.camera.unproject(pos, 0.0);
+        self.aim([pos[0], pos[1]]);
+    }
+
+    fn mouse_press(&mut self,  button: MouseButton) {
+        self.input_press(Input::Mouse(button));
     }
 
-    fn mouse_press(&mut self,  _: MouseButton) {
-        self.start_pause()
+    fn mouse_release(&mut self,  button: MouseButton) {
+        self.input_release(Input::Mouse(button));
+    }
+
+    fn touch(&mut self,  id: u64,  phase: TouchPhase,  pos: [f32; 2]) {
+        match self.touches.touch(id, phase, pos) {
+            TouchAction::Drag(moved) => {
+                self.control = Control::Pointer;
+                // move relative to where the racket is, so that the finger doesn't hide it
+                let scale = self.camera.scale(self.camera.depth([0.0, 0.0, 0.0]));
+                self.aim([
+                    self.player_target[0] + moved[0]/scale,
+                    self.player_target[1] + moved[1]/scale,
+                ]);
+            }
+            TouchAction::Tap if self.state == State::Records  ||  self.state == State::Summary => self.close_screen(),
+            TouchAction::Tap if self.state != State::Settings => self.start_pause(),
+            TouchAction::Tap => {}
+            TouchAction::Nothing => {}
+        }
     }
 
     fn key_press(&mut self,  key: Key) {
         // println!("key pressed: {:?}", key);
-        match key {
-            Key::ArrowUp => {
-                self.keys.up = true;
-                self.player_target[1] = RACKET_SIZE[1]/2.0;
-            },
-            Key::ArrowDown => {
-                self.keys.down = true;
-                self.player_target[1] = ARENA[1]-RACKET_SIZE[1]/2.0;
-            },
-            Key::ArrowLeft => {
-                self.keys.left = true;
-                self.player_target[0] = RACKET_SIZE[0]/2.0;
-            },
-            Key::ArrowRight => {
-                self.keys.right = true;
-                self.player_target[0] = ARENA[0]-RACKET_SIZE[0]/2.0;
-            },
-            // pausing with enter is a bit weird,
-            // but it's nice since it's close to the arrow keys. (and consistency)
-            Key::Space | Key::Enter => self.start_pause(),
-            Key::Escape => {
-                // starting with escape feels weird
-                self.state = match self.state {
-                    State::Playing => State::Paused,
-                    State::Paused => State::Playing,
-                    other => other
-                };
-            },
-        }
+        self.input_press(Input::Key(key));
     }
 
     fn key_release(&mut self,  key: Key) {
         // println!("key released: {:?}", key);
-        match key {
-            Key::ArrowUp => {
-                self.keys.up = false;
-                self.player_target[1] = if self.keys.down {
-                    ARENA[1]-RACKET_SIZE[1]/2.0
-                } else {
-                    self.player_pos[1]
-                };
-            },
-            Key::ArrowDown => {
-                self.keys.down = false;
-                self.player_target[1] = if self.keys.up {
-                    RACKET_SIZE[1]/2.0
-                } else {
-                    self.player_pos[1]
-                };
-            },
-            Key::ArrowLeft => {
-                self.keys.left = false;
-                self.player_target[0] = if self.keys.right {
-                    ARENA[0]-RACKET_SIZE[0]/2.0
-                } else {
-                    self.player_pos[0]
-                };
-            },
-            Key::ArrowRight => {
-                self.keys.right = false;
-                self.player_target[0] = if self.keys.left {
-                    RACKET_SIZE[0]/2.0
-                } else {
-                    self.player_pos[0]
-                };
-            },
-            _ => {}
+        self.input_release(Input::Key(key));
+    }
+
+    fn watched_files(&self) -> &'static [&'static str] {
+        &[TUNING_FILE, THEMES_DIR]
+    }
+
+    fn file_changed(&mut self,  path: &'static str) {
+        if path == TUNING_FILE {
+            match Tuning::load() {
+                Ok(tuning) => self.retune(tuning),
+                Err(e) => eprintln!("Cannot reload {}: {} - keeping the previous values", TUNING_FILE, e),
+            }
+        } else if path == THEMES_DIR {
+            self.themes = load_themes();
+            self.update_theme();
         }
     }
 }
@@ -0,0 +1,226 @@
+//! The records screen, and the summary shown after each match.
+
+use super::{Action, SpaceTennis, State};
+use super::stats::{MatchStats, Side};
+use super::theme::Paint;
+use ::interface::game::*;
+use ::interface::input::*;
+use ::interface::locale::Localization;
+use std::cmp::Ordering;
+
+/// Entries on each leaderboard of the records screen.
+const RECORDS_SHOWN: usize = 5;
+
+/// The wins in a row each player had after each match.
+fn win_streaks(all: &[MatchStats]) -> impl Iterator<Item=(&str, u32)> {
+    let mut streaks = Vec::<(&str, u32)>::new();
+    all.iter().map(move |stats| {
+        let player = stats.player.as_str();
+        let index = match streaks.iter().position(|&(name, _)| name == player) {
+            Some(index) => index,
+            None => {
+                streaks.push((player, 0));
+                streaks.len() - 1
+            }
+        };
+        let streak = &mut streaks[index].1;
+        *streak = if stats.won() {*streak + 1} else {0};
+        (player, *streak)
+    })
+}
+
+/// The best value of each player, best first,
+/// where ties go to whoever got there first.
+fn leaderboard<'a, T: Copy + PartialOrd>(values: impl Iterator<Item=(&'a str, T)>,  higher_is_better: bool)
+-> Vec<(&'a str, T)> {
+    // with when it was reached, for breaking ties
+    let mut best = Vec::<(&str, T, usize)>::new();
+    for (i, (player, value)) in values.enumerate() {
+        let better = |old: T| if higher_is_better {value > old} else {value < old};
+        match best.iter_mut().find(|(name, _, _)| *name == player) {
+            Some(entry) => if better(entry.1) {
+                *entry = (player, value, i);
+            },
+            None => best.push((player, value, i)),
+        }
+    }
+    best.sort_by(|a, b| {
+        let by_value = a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal);
+        let by_value = if higher_is_better {by_value.reverse()} else {by_value};
+        by_value.then(a.2.cmp(&b.2))
+    });
+    best.into_iter().map(|(player, value, _)| (player, value)).collect()
+}
+
+impl SpaceTennis {
+    pub(super) fn open_records(&mut self,  then: State) {
+        self.records_return = then;
+        self.state = State::Records;
+    }
+
+    /// Leave the summary or records screen.
+    pub(super) fn close_screen(&mut self) {
+        match self.state {
+            State::Summary => self.open_records(State::PlayerStart),
+            _ => self.state = self.records_return,
+        }
+    }
+
+    /// Close the summary or records screen with any of the obvious inputs.
+    pub(super) fn screen_input(&mut self,  input: Input) {
+        match (input, self.bindings.action(input)) {
+            (Input::Key(Key::Enter), _) | (Input::Key(Key::Escape), _) | (Input::Key(Key::Tab), _)
+            | (Input::Mouse(MouseButton::Left), _) | (_, Some(Action::Serve)) | (_, Some(Action::Pause)) | (_, Some(Action::Settings)) => {
+                self.close_screen();
+            }
+            _ => {}
+        }
+    }
+
+    /// The best results of each player, with the current player highlighted.
+    pub(super) fn draw_records(&self,  gfx: &mut Graphics) {
+        let strings = &self.strings;
+        let theme = &self.current_theme;
+        let text_color = theme[Paint::SettingsText];
+        let player_color = theme[Paint::SettingsSelected];
+        let panel = [0.05, 0.05, 0.9, 0.9];
+        gfx.rounded_rectangle(theme[Paint::SettingsBackground], panel, 0.03);
+        gfx.stroke(text_color, 0.004, Outline::RoundedRectangle{ area: panel,  radius: 0.03 });
+        let title = strings.text("records.title");
+        gfx.text(text_color, [0.5, 0.1], (Align::Center, VAlign::Center), 0.06, title);
+
+        let matches = &self.stats_log;
+        fn counted<'a>(entries: Vec<(&'a str, u32)>,  key: &str,  strings: &Localization) -> Vec<(&'a str, String)> {
+            entries.into_iter().map(|(player, count)| (player, strings.plural(key, count.into(), &[]))).collect()
+        }
+        let top_speeds = leaderboard(matches.iter().map(|stats| (stats.player.as_str(), stats.max_speed)), true);
+        let boards = [
+            ("records.longest_rally", counted(
+                    leaderboard(matches.iter().map(|stats| (stats.player.as_str(), stats.longest_rally())), true),
+                    "records.hits", strings,
+            )),
+            ("records.top_speed", top_speeds.into_iter()
+                .map(|(player, speed)| (player, strings.format("records.speed", &[("speed", &speed)])))
+                .collect()),
+            ("records.fewest_misses", counted(
+                    leaderboard(matches.iter().map(|stats| (stats.player.as_str(), stats.misses(Side::Player))), false),
+                    "records.misses", strings,
+            )),
+            ("records.win_streak", counted(
+                    leaderboard(win_streaks(matches).filter(|&(_, streak)| streak > 0), true),
+                    "records.wins", strings,
+            )),
+        ];
+        for (i, (title, entries)) in boards.into_iter().enumerate() {
+            let (x, top) = (0.1 + 0.42*(i%2) as f32,  0.2 + 0.34*(i/2) as f32);
+            gfx.text(text_color, [x, top], (Align::Left, VAlign::Center), 0.04, strings.text(title));
+            if entries.is_empty() {
+                let empty = strings.text("records.empty");
+                gfx.text(text_color, [x, top+0.06], (Align::Left, VAlign::Center), 0.03, empty);
+            }
+            for (rank, (player, value)) in entries.into_iter().take(RECORDS_SHOWN).enumerate() {
+                let y = top + 0.06 + 0.045*rank as f32;
+                let color = if player == self.player {player_color} else {text_color};
+                let name = format!("{}. {}", rank+1, player);
+                gfx.text(color, [x, y], (Align::Left, VAlign::Center), 0.03, name);
+                gfx.text(color, [x+0.36, y], (Align::Right, VAlign::Center), 0.03, value);
+            }
+        }
+        gfx.text(text_color, [0.5, 0.9], (Align::Center, VAlign::Center), 0.03, strings.text("records.help"));
+    }
+
+    /// The statistics of the match that just ended.
+    pub(super) fn draw_summary(&self,  stats: &MatchStats,  gfx: &mut Graphics) {
+        let strings = &self.strings;
+        let theme = &self.current_theme;
+        let text_color = theme[Paint::SettingsText];
+        let panel = [0.05, 0.05, 0.9, 0.9];
+        gfx.rounded_rectangle(theme[Paint::SettingsBackground], panel, 0.03);
+        gfx.stroke(text_color, 0.004, Outline::RoundedRectangle{ area: panel,  radius: 0.03 });
+        let title = strings.text("summary.title");
+        gfx.text(text_color, [0.5, 0.1], (Align::Center, VAlign::Center), 0.06, title);
+        let result = strings.format(
+                if stats.won() {"summary.won"} else {"summary.lost"},
+                &[("player", &stats.misses(Side::Opponent)), ("opponent", &stats.misses(Side::Player))],
+        );
+        gfx.text(theme[Paint::SettingsSelected], [0.5, 0.17], (Align::Center, VAlign::Center), 0.04, result);
+
+        // a column for each side, then values for the whole match between them
+        let (player_x, opponent_x, match_x) = (0.6, 0.8, 0.7);
+        let per_side = [
+            ("summary.hits", Side::ALL.map(|side| stats.hits(side))),
+            ("summary.serves", Side::ALL.map(|side| stats.serves(side))),
+            ("summary.serves_won", Side::ALL.map(|side| stats.serves_won(side))),
+            ("summary.aces", Side::ALL.map(|side| stats.aces(side))),
+        ];
+        let average = |average: f32| strings.format("summary.average", &[("average", &average)]);
+        let speed = |speed: f32| strings.format("records.speed", &[("speed", &speed)]);
+        let mut whole_match = vec![
+            ("summary.rallies", stats.rallies.len().to_string()),
+            ("summary.longest_rally", strings.plural("records.hits", stats.longest_rally().into(), &[])),
+            ("summary.average_rally", average(stats.average_rally())),
+            ("summary.wall_bounces", average(stats.average_wall_bounces())),
+            ("summary.top_speed", speed(stats.max_speed)),
+            ("summary.average_speed", speed(stats.average_speed())),
+        ];
+        for (state, seconds) in State::TIMED.into_iter().zip(stats.seconds) {
+            let label = match state {
+                State::Playing => "summary.time.playing",
+                State::Paused => "summary.time.paused",
+                State::PlayerStart => "summary.time.player_start",
+                State::OpponentStart => "summary.time.opponent_start",
+                _ => "summary.time.settings",
+            };
+            whole_match.push((label, strings.format("summary.seconds", &[("seconds", &seconds)])));
+        }
+        let rows = 1 + per_side.len() + whole_match.len();
+        let row_height = 0.6/rows as f32;
+        let size = row_height*0.7;
+        let row_y = |row: usize| 0.25 + row_height*row as f32;
+        let centered = (Align::Center, VAlign::Center);
+        gfx.text(text_color, [player_x, row_y(0)], centered, size, stats.player.clone());
+        gfx.text(text_color, [opponent_x, row_y(0)], centered, size, strings.text("summary.opponent"));
+        for (row, (label, [player, opponent])) in per_side.into_iter().enumerate() {
+            let y = row_y(1 + row);
+            gfx.text(text_color, [0.1, y], (Align::Left, VAlign::Center), size, strings.text(label));
+            gfx.text(text_color, [player_x, y], centered, size, player.to_string());
+            gfx.text(text_color, [opponent_x, y], centered, size, opponent.to_string());
+        }
+        for (row, (label, value)) in whole_match.into_iter().enumerate() {
+            let y = row_y(1 + per_side.len() + row);
+            gfx.text(text_color, [0.1, y], (Align::Left, VAlign::Center), size, strings.text(label));
+            gfx.text(text_color, [match_x, y], centered, size, value);
+        }
+        gfx.text(text_color, [0.5, 0.9], centered, 0.03, strings.text("summary.help"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::stats::Rally;
+
+    /// A match of one rally that `player` won or lost.
+    fn result(player: &str,  won: bool) -> MatchStats {
+        let missed_by = if won {Side::Opponent} else {Side::Player};
+        MatchStats {
+            player: player.to_string(),
+            rallies: vec![Rally { missed_by,  ..Rally::serve(Side::Player) }],
+            ..MatchStats::default()
+        }
+    }
+
+    #[test]
+    fn streaks() {
+        let all = [result("BO", true), result("ANNA", false), result("BO", true), result("BO", true), result("ANNA", false)];
+        let streaks = win_streaks(&all).collect::<Vec<_>>();
+        assert_eq!(streaks, [("BO", 1), ("ANNA", 0), ("BO", 2), ("BO", 3), ("ANNA", 0)]);
+    }
+
+    #[test]
+    fn leaderboards() {
+        let values = [("ANNA", 2), ("BO", 3), ("CAL", 3), ("ANNA", 1)];
+        assert_eq!(leaderboard(values.into_iter(), true), [("BO", 3), ("CAL", 3), ("ANNA", 2)]);
+        assert_eq!(leaderboard(values.into_iter(), false), [("ANNA", 1), ("BO", 3), ("CAL", 3)]);
+    }
+}
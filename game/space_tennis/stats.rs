@@ -0,0 +1,425 @@
+//! What happens in each match, saved for the records screen and `--export-stats`.
+
+use super::{valid_player_name, State};
+use super::tuning::Setting;
+use ::interface::storage::{load_migrated, save_versioned, Category, Storage};
+use std::fmt::Write;
+use std::io::{self, ErrorKind};
+
+const STATS_VERSION: u32 = 1;
+
+/// Which end of the arena.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub enum Side {Player, Opponent}
+
+impl Side {
+    pub const ALL: [Side; 2] = [Side::Player, Side::Opponent];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Side::Player => "player",
+            Side::Opponent => "opponent",
+        }
+    }
+}
+
+impl Setting for Side {
+    fn parse(value: &str) -> Result<Self, String> {
+        Side::ALL.into_iter().find(|side| side.name() == value)
+            .ok_or_else(|| format!("{:?} is not player or opponent", value))
+    }
+}
+
+/// A serve and what happened until someone missed.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub struct Rally {
+    pub server: Side,
+    /// Racket hits by each side, indexed by `Side`.
+    pub hits: [u32; 2],
+    pub wall_bounces: u32,
+    pub missed_by: Side,
+}
+
+impl Rally {
+    /// A rally that has just started, whose `missed_by` is set when it ends.
+    pub fn serve(server: Side) -> Self {
+        Rally { server,  hits: [0, 0],  wall_bounces: 0,  missed_by: server }
+    }
+
+    /// Parse a line like `player, 2, 3, 4, opponent`:
+    /// the server, the hits of each side, the wall bounces and who missed.
+    fn parse(line: &str) -> Result<Self, String> {
+        let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+        let [server, player_hits, opponent_hits, wall_bounces, missed_by] = fields[..] else {
+            return Err(format!("has {} fields instead of 5", fields.len()));
+        };
+        Ok(Rally {
+            server: Side::parse(server)?,
+            hits: [u32::parse(player_hits)?, u32::parse(opponent_hits)?],
+            wall_bounces: u32::parse(wall_bounces)?,
+            missed_by: Side::parse(missed_by)?,
+        })
+    }
+
+    fn line(&self) -> String {
+        format!("{}, {}, {}, {}, {}",
+            self.server.name(),
+            self.hits[Side::Player as usize],
+            self.hits[Side::Opponent as usize],
+            self.wall_bounces,
+            self.missed_by.name(),
+        )
+    }
+
+    fn total_hits(&self) -> u32 {
+        self.hits.iter().sum()
+    }
+
+    /// Whether the serve was never returned.
+    fn is_ace(&self) -> bool {
+        self.missed_by != self.server  &&  self.total_hits() == 0
+    }
+}
+
+/// The average, or zero if there are no values.
+fn average(values: impl Iterator<Item=u32>) -> f32 {
+    let (sum, count) = values.fold((0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {0.0} else {sum as f32 / count as f32}
+}
+
+/// What happened in a match, for the summary and records screens and `--export-stats`.
+///
+/// They are saved as lines of `key = value`, where each match starts with a `player` line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchStats {
+    pub player: String,
+    pub rallies: Vec<Rally>,
+    pub max_speed: f32,
+    /// How far the ball moved while playing, for the average speed.
+    pub ball_distance: f32,
+    /// Indexed like `State::TIMED`.
+    pub seconds: [f32; State::TIMED.len()],
+}
+
+impl MatchStats {
+    pub fn misses(&self,  side: Side) -> u32 {
+        self.rallies.iter().filter(|rally| rally.missed_by == side).count() as u32
+    }
+
+    pub fn won(&self) -> bool {
+        self.misses(Side::Opponent) > self.misses(Side::Player)
+    }
+
+    pub fn hits(&self,  side: Side) -> u32 {
+        self.rallies.iter().map(|rally| rally.hits[side as usize]).sum()
+    }
+
+    pub fn serves(&self,  side: Side) -> u32 {
+        self.rallies.iter().filter(|rally| rally.server == side).count() as u32
+    }
+
+    /// Serves where the other side missed.
+    pub fn serves_won(&self,  side: Side) -> u32 {
+        self.rallies.iter().filter(|rally| rally.server == side && rally.missed_by != side).count() as u32
+    }
+
+    pub fn aces(&self,  side: Side) -> u32 {
+        self.rallies.iter().filter(|rally| rally.server == side && rally.is_ace()).count() as u32
+    }
+
+    pub fn longest_rally(&self) -> u32 {
+        self.rallies.iter().map(Rally::total_hits).max().unwrap_or(0)
+    }
+
+    pub fn average_rally(&self) -> f32 {
+        average(self.rallies.iter().map(Rally::total_hits))
+    }
+
+    pub fn average_wall_bounces(&self) -> f32 {
+        average(self.rallies.iter().map(|rally| rally.wall_bounces))
+    }
+
+    fn seconds_in(&self,  state: State) -> f32 {
+        State::TIMED.iter().position(|&timed| timed == state).map_or(0.0, |index| self.seconds[index])
+    }
+
+    pub fn average_speed(&self) -> f32 {
+        let playing = self.seconds_in(State::Playing);
+        if playing > 0.0 {self.ball_distance / playing} else {0.0}
+    }
+
+    /// Everything that is summed up from the rallies, named as in the exports.
+    fn totals(&self) -> Vec<(String, f32)> {
+        let mut totals = Vec::new();
+        for side in Side::ALL {
+            totals.push((format!("{}_misses", side.name()), self.misses(side) as f32));
+            totals.push((format!("{}_hits", side.name()), self.hits(side) as f32));
+            totals.push((format!("{}_serves", side.name()), self.serves(side) as f32));
+            totals.push((format!("{}_serves_won", side.name()), self.serves_won(side) as f32));
+            totals.push((format!("{}_aces", side.name()), self.aces(side) as f32));
+        }
+        totals.push(("rally_count".to_string(), self.rallies.len() as f32));
+        totals.push(("longest_rally".to_string(), self.longest_rally() as f32));
+        totals.push(("average_rally".to_string(), self.average_rally()));
+        totals.push(("average_wall_bounces".to_string(), self.average_wall_bounces()));
+        totals.push(("max_speed".to_string(), self.max_speed));
+        totals.push(("average_speed".to_string(), self.average_speed()));
+        for (state, seconds) in State::TIMED.into_iter().zip(self.seconds) {
+            totals.push((format!("seconds_{}", state.name()), seconds));
+        }
+        totals
+    }
+
+    fn parse_all(contents: &str) -> Result<Vec<Self>, String> {
+        let mut all = Vec::<MatchStats>::new();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: missing =", i+1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let line_error = |e: String| format!("line {}: {}", i+1, e);
+            if key == "player" {
+                let player = valid_player_name(value)
+                    .ok_or_else(|| line_error(format!("{:?} is not a valid name", value)))?;
+                all.push(MatchStats { player,  ..MatchStats::default() });
+                continue;
+            }
+            let stats = all.last_mut().ok_or_else(|| line_error(format!("{} before player", key)))?;
+            match key {
+                "rally" => stats.rallies.push(Rally::parse(value).map_err(line_error)?),
+                "max_speed" => stats.max_speed = f32::parse(value).map_err(line_error)?,
+                "ball_distance" => stats.ball_distance = f32::parse(value).map_err(line_error)?,
+                _ => {
+                    let timed = key.strip_prefix("seconds.")
+                        .and_then(|state| State::TIMED.iter().position(|timed| timed.name() == state));
+                    match timed {
+                        Some(index) => stats.seconds[index] = f32::parse(value).map_err(line_error)?,
+                        None => return Err(line_error(format!("unknown stat {:?}", key))),
+                    }
+                }
+            }
+        }
+        Ok(all)
+    }
+
+    fn write(&self,  out: &mut String) {
+        writeln!(out, "player = {}", self.player).unwrap();
+        writeln!(out, "max_speed = {}", self.max_speed).unwrap();
+        writeln!(out, "ball_distance = {}", self.ball_distance).unwrap();
+        for (state, seconds) in State::TIMED.into_iter().zip(self.seconds) {
+            writeln!(out, "seconds.{} = {}", state.name(), seconds).unwrap();
+        }
+        for rally in &self.rallies {
+            writeln!(out, "rally = {}", rally.line()).unwrap();
+        }
+    }
+}
+
+/// Change statistics saved in `version` to the format of the next version.
+fn migrate_stats(version: u32,  contents: String) -> Result<String, String> {
+    match version {
+        // they have always been saved with a version, so the line must have been removed
+        0 => Ok(contents),
+        _ => Err(format!("unknown version {}", version)),
+    }
+}
+
+pub fn load_stats(storage: &dyn Storage) -> io::Result<Vec<MatchStats>> {
+    match load_migrated(storage, Category::Data, "stats", STATS_VERSION, migrate_stats)? {
+        Some(contents) => MatchStats::parse_all(&contents).map_err(|e| io::Error::new(ErrorKind::InvalidData, e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+pub fn save_stats(storage: &mut dyn Storage,  all: &[MatchStats]) -> io::Result<()> {
+    let mut contents = String::from("# rally = server, player hits, opponent hits, wall bounces, missed by\n");
+    for stats in all {
+        contents.push('\n');
+        stats.write(&mut contents);
+    }
+    save_versioned(storage, Category::Data, "stats", STATS_VERSION, &contents)
+}
+
+/// A line for each match, with the totals but not the rallies.
+pub fn stats_csv(all: &[MatchStats]) -> String {
+    let mut csv = String::from("match,player,won");
+    for (name, _) in MatchStats::default().totals() {
+        write!(csv, ",{}", name).unwrap();
+    }
+    csv.push('\n');
+    for (i, stats) in all.iter().enumerate() {
+        write!(csv, "{},{},{}", i+1, stats.player, stats.won()).unwrap();
+        for (_, value) in stats.totals() {
+            write!(csv, ",{}", value).unwrap();
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// An array with an object for each match, with the totals and the rallies.
+pub fn stats_json(all: &[MatchStats]) -> String {
+    // player names are only letters and spaces, so nothing needs escaping
+    let mut json = String::from("[");
+    for (i, stats) in all.iter().enumerate() {
+        let separator = if i == 0 {""} else {","};
+        write!(json, "{}\n  {{\"match\": {}, \"player\": \"{}\", \"won\": {}", separator, i+1, stats.player, stats.won()).unwrap();
+        for (name, value) in stats.totals() {
+            write!(json, ", \"{}\": {}", name, value).unwrap();
+        }
+        json.push_str(", \"rallies\": [");
+        for (r, rally) in stats.rallies.iter().enumerate() {
+            write!(json,
+                "{}\n    {{\"server\": \"{}\", \"player_hits\": {}, \"opponent_hits\": {}, \"wall_bounces\": {}, \"missed_by\": \"{}\"}}",
+                if r == 0 {""} else {","},
+                rally.server.name(),
+                rally.hits[Side::Player as usize],
+                rally.hits[Side::Opponent as usize],
+                rally.wall_bounces,
+                rally.missed_by.name(),
+            ).unwrap();
+        }
+        json.push_str(if stats.rallies.is_empty() {"]}"} else {"\n  ]}"});
+    }
+    json.push_str(if all.is_empty() {"]\n"} else {"\n]\n"});
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::interface::storage::MemoryStorage;
+
+    fn rally(server: Side,  hits: [u32; 2],  wall_bounces: u32,  missed_by: Side) -> Rally {
+        Rally { server,  hits,  wall_bounces,  missed_by }
+    }
+
+    /// A match that was lost, with an ace by the player.
+    fn lost() -> MatchStats {
+        MatchStats {
+            player: "ANNA".to_string(),
+            rallies: vec![
+                rally(Side::Player, [0, 0], 1, Side::Opponent),
+                rally(Side::Opponent, [2, 1], 3, Side::Player),
+                rally(Side::Player, [1, 1], 0, Side::Player),
+            ],
+            max_speed: 1.5,
+            ball_distance: 6.0,
+            seconds: [4.0, 1.0, 0.5, 0.5, 0.0],
+        }
+    }
+
+    /// A match that was won because the opponent missed its own serve.
+    fn won() -> MatchStats {
+        MatchStats {
+            player: "BO".to_string(),
+            rallies: vec![rally(Side::Opponent, [0, 0], 0, Side::Opponent)],
+            max_speed: 0.25,
+            ball_distance: 0.0,
+            seconds: [0.0; State::TIMED.len()],
+        }
+    }
+
+    #[test]
+    fn stats_round_trip() {
+        let mut storage = MemoryStorage::default();
+        assert_eq!(load_stats(&storage).unwrap(), Vec::new());
+        let all = vec![lost(), won(), MatchStats { player: "ANNA".to_string(),  ..MatchStats::default() }];
+        save_stats(&mut storage, &all).unwrap();
+        assert_eq!(load_stats(&storage).unwrap(), all);
+        save_stats(&mut storage, &[]).unwrap();
+        assert_eq!(load_stats(&storage).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn stats_errors() {
+        assert_eq!(MatchStats::parse_all("max_speed = 1"), Err("line 1: max_speed before player".to_string()));
+        assert_eq!(MatchStats::parse_all("player = anna"), Err("line 1: \"anna\" is not a valid name".to_string()));
+        let unknown = MatchStats::parse_all("# comment\nplayer = ANNA\nseconds.sleeping = 1");
+        assert_eq!(unknown, Err("line 3: unknown stat \"seconds.sleeping\"".to_string()));
+        let rally = MatchStats::parse_all("player = ANNA\nrally = player, 1, 2, 3");
+        assert_eq!(rally, Err("line 2: has 4 fields instead of 5".to_string()));
+    }
+
+    #[test]
+    fn serves_and_aces() {
+        let stats = lost();
+        assert_eq!(Side::ALL.map(|side| stats.serves(side)), [2, 1]);
+        assert_eq!(Side::ALL.map(|side| stats.serves_won(side)), [1, 1]);
+        assert_eq!(Side::ALL.map(|side| stats.aces(side)), [1, 0]);
+        assert_eq!(Side::ALL.map(|side| stats.misses(side)), [2, 1]);
+        assert_eq!(Side::ALL.map(|side| stats.hits(side)), [3, 2]);
+        assert_eq!(stats.longest_rally(), 3);
+        assert!(!stats.won());
+        // missing your own serve is not an ace
+        let stats = won();
+        assert_eq!(Side::ALL.map(|side| stats.serves_won(side)), [0, 0]);
+        assert_eq!(Side::ALL.map(|side| stats.aces(side)), [0, 0]);
+        assert!(stats.won());
+    }
+
+    #[test]
+    fn export_nothing() {
+        assert_eq!(stats_json(&[]), "[]\n");
+        assert_eq!(stats_csv(&[]), CSV_HEADER);
+    }
+
+    const CSV_HEADER: &str = "match,player,won,\
+        player_misses,player_hits,player_serves,player_serves_won,player_aces,\
+        opponent_misses,opponent_hits,opponent_serves,opponent_serves_won,opponent_aces,\
+        rally_count,longest_rally,average_rally,average_wall_bounces,max_speed,average_speed,\
+        seconds_playing,seconds_paused,seconds_player_start,seconds_opponent_start,seconds_settings\n";
+
+    const LOST_JSON: &str = concat!(r#"{"match": 1, "player": "ANNA", "won": false, "#,
+        r#""player_misses": 2, "player_hits": 3, "player_serves": 2, "player_serves_won": 1, "player_aces": 1, "#,
+        r#""opponent_misses": 1, "opponent_hits": 2, "opponent_serves": 1, "opponent_serves_won": 1, "opponent_aces": 0, "#,
+        r#""rally_count": 3, "longest_rally": 3, "average_rally": 1.6666666, "average_wall_bounces": 1.3333334, "#,
+        r#""max_speed": 1.5, "average_speed": 1.5, "#,
+        r#""seconds_playing": 4, "seconds_paused": 1, "seconds_player_start": 0.5, "seconds_opponent_start": 0.5, "seconds_settings": 0, "#,
+        r#""rallies": [
+    {"server": "player", "player_hits": 0, "opponent_hits": 0, "wall_bounces": 1, "missed_by": "opponent"},
+    {"server": "opponent", "player_hits": 2, "opponent_hits": 1, "wall_bounces": 3, "missed_by": "player"},
+    {"server": "player", "player_hits": 1, "opponent_hits": 1, "wall_bounces": 0, "missed_by": "player"}
+  ]}"#);
+
+    const LOST_CSV: &str = "1,ANNA,false,2,3,2,1,1,1,2,1,1,0,3,3,1.6666666,1.3333334,1.5,1.5,4,1,0.5,0.5,0\n";
+
+    #[test]
+    fn export_one() {
+        assert_eq!(stats_json(&[lost()]), format!("[\n  {}\n]\n", LOST_JSON));
+        assert_eq!(stats_csv(&[lost()]), format!("{}{}", CSV_HEADER, LOST_CSV));
+    }
+
+    #[test]
+    fn export_several() {
+        let won_json = concat!(r#"{"match": 2, "player": "BO", "won": true, "#,
+            r#""player_misses": 0, "player_hits": 0, "player_serves": 0, "player_serves_won": 0, "player_aces": 0, "#,
+            r#""opponent_misses": 1, "opponent_hits": 0, "opponent_serves": 1, "opponent_serves_won": 0, "opponent_aces": 0, "#,
+            r#""rally_count": 1, "longest_rally": 0, "average_rally": 0, "average_wall_bounces": 0, "#,
+            r#""max_speed": 0.25, "average_speed": 0, "#,
+            r#""seconds_playing": 0, "seconds_paused": 0, "seconds_player_start": 0, "seconds_opponent_start": 0, "seconds_settings": 0, "#,
+            r#""rallies": [
+    {"server": "opponent", "player_hits": 0, "opponent_hits": 0, "wall_bounces": 0, "missed_by": "opponent"}
+  ]}"#);
+        let no_rallies_json = concat!(r#"{"match": 3, "player": "BO", "won": false, "#,
+            r#""player_misses": 0, "player_hits": 0, "player_serves": 0, "player_serves_won": 0, "player_aces": 0, "#,
+            r#""opponent_misses": 0, "opponent_hits": 0, "opponent_serves": 0, "opponent_serves_won": 0, "opponent_aces": 0, "#,
+            r#""rally_count": 0, "longest_rally": 0, "average_rally": 0, "average_wall_bounces": 0, "#,
+            r#""max_speed": 0, "average_speed": 0, "#,
+            r#""seconds_playing": 0, "seconds_paused": 0, "seconds_player_start": 0, "seconds_opponent_start": 0, "seconds_settings": 0, "#,
+            r#""rallies": []}"#);
+        let all = [lost(), won(), MatchStats { player: "BO".to_string(),  ..MatchStats::default() }];
+        let json = format!("[\n  {},\n  {},\n  {}\n]\n", LOST_JSON, won_json, no_rallies_json);
+        assert_eq!(stats_json(&all), json);
+        let csv = format!("{}{}{}{}",
+            CSV_HEADER,
+            LOST_CSV,
+            "2,BO,true,0,0,0,0,0,1,0,1,0,0,1,0,0,0,0.25,0,0,0,0,0,0\n",
+            "3,BO,false,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0\n",
+        );
+        assert_eq!(stats_csv(&all), csv);
+    }
+}
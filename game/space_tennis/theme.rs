@@ -0,0 +1,181 @@
+//! The colors of the game, which can be changed with palette files in `THEMES_DIR`.
+
+use ::interface::game::Color;
+use ::interface::palette::Palette;
+use std::io::ErrorKind;
+use std::ops::Index;
+use std::path::Path;
+
+pub const THEMES_DIR: &str = "themes";
+// the first is the default
+pub const THEMES: [(&str, &str); 6] = [
+    ("dark", include_str!("../../themes/dark.txt")),
+    ("light", include_str!("../../themes/light.txt")),
+    ("retro_green", include_str!("../../themes/retro_green.txt")),
+    ("high_contrast", include_str!("../../themes/high_contrast.txt")),
+    ("colorblind", include_str!("../../themes/colorblind.txt")),
+    ("colorblind_light", include_str!("../../themes/colorblind_light.txt")),
+];
+
+/// Every color in the game, named after what it's used for.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub enum Paint {
+    /// Behind the stars.
+    Background,
+    /// Tint of the star image.
+    Stars,
+    /// Tint of the logo image.
+    Logo,
+    /// The near end of the walls.
+    Wall,
+    WallFar,
+    WallLine,
+    Racket,
+    RacketBorder,
+    Ball,
+    /// Marks on the walls at the depth of the ball.
+    BallLine,
+    Miss,
+    /// The lit part of miss balls.
+    MissHighlight,
+    /// Set, speed and start messages.
+    Text,
+    Pause,
+    Debug,
+    SettingsBackground,
+    SettingsText,
+    SettingsSelected,
+}
+
+impl Paint {
+    pub const ALL: [Paint; 18] = [
+        Paint::Background,
+        Paint::Stars,
+        Paint::Logo,
+        Paint::Wall,
+        Paint::WallFar,
+        Paint::WallLine,
+        Paint::Racket,
+        Paint::RacketBorder,
+        Paint::Ball,
+        Paint::BallLine,
+        Paint::Miss,
+        Paint::MissHighlight,
+        Paint::Text,
+        Paint::Pause,
+        Paint::Debug,
+        Paint::SettingsBackground,
+        Paint::SettingsText,
+        Paint::SettingsSelected,
+    ];
+
+    /// The name used in palette files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Paint::Background => "background",
+            Paint::Stars => "stars",
+            Paint::Logo => "logo",
+            Paint::Wall => "wall",
+            Paint::WallFar => "wall_far",
+            Paint::WallLine => "wall_line",
+            Paint::Racket => "racket",
+            Paint::RacketBorder => "racket_border",
+            Paint::Ball => "ball",
+            Paint::BallLine => "ball_line",
+            Paint::Miss => "miss",
+            Paint::MissHighlight => "miss_highlight",
+            Paint::Text => "text",
+            Paint::Pause => "pause",
+            Paint::Debug => "debug",
+            Paint::SettingsBackground => "settings_background",
+            Paint::SettingsText => "settings_text",
+            Paint::SettingsSelected => "settings_selected",
+        }
+    }
+}
+
+/// A palette with a color for every `Paint`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// The file name in `THEMES_DIR` without extension.
+    pub name: &'static str,
+    pub colors: [Color; Paint::ALL.len()],
+}
+
+impl Theme {
+    pub fn new(name: &'static str,  palette: &Palette) -> Result<Self, String> {
+        let known = |color: &str| Paint::ALL.iter().any(|paint| paint.name() == color);
+        if let Some(unknown) = palette.names().find(|&color| !known(color)) {
+            return Err(format!("unknown color {:?}", unknown));
+        }
+        let mut colors = [Color::default(); Paint::ALL.len()];
+        for paint in Paint::ALL {
+            colors[paint as usize] = palette.get(paint.name())
+                .ok_or_else(|| format!("{} is missing", paint.name()))?;
+        }
+        Ok(Theme { name,  colors })
+    }
+
+    /// The built-in palette, or the one in `THEMES_DIR` if it exists.
+    pub fn load(name: &'static str,  built_in: &str) -> Self {
+        let palette = Palette::parse(name, built_in).expect("parse built-in theme");
+        let theme = Theme::new(name, &palette).expect("built-in theme has every color");
+        let path = Path::new(THEMES_DIR).join(format!("{}.txt", name));
+        match Palette::load(name, &path).map(|palette| Theme::new(name, &palette)) {
+            Ok(Ok(changed)) => changed,
+            Ok(Err(e)) => {
+                eprintln!("Cannot use {}: {}", path.display(), e);
+                theme
+            }
+            Err(ref e) if e.kind() == ErrorKind::NotFound => theme,
+            Err(e) => {
+                eprintln!("Cannot load {}: {}", path.display(), e);
+                theme
+            }
+        }
+    }
+}
+
+impl Theme {
+    /// Foreground colors made opaque and changed until they stand out clearly
+    /// from what they're drawn on, and no stars.
+    pub fn high_contrast(&self) -> Self {
+        // WCAG's recommendation for normal text at its highest level
+        const MIN_CONTRAST: f32 = 7.0;
+        fn stand_out(color: Color,  against: Color) -> Color {
+            let color = color.with_alpha(1.0);
+            let lighten = Color::WHITE.contrast_ratio(against) > Color::BLACK.contrast_ratio(against);
+            (0..=10).map(|step| step as f32 / 10.0)
+                .map(|amount| if lighten {color.lighten(amount)} else {color.darken(amount)})
+                .find(|changed| changed.contrast_ratio(against) >= MIN_CONTRAST)
+                .unwrap_or(if lighten {Color::WHITE} else {Color::BLACK})
+        }
+        let mut theme = self.clone();
+        theme.colors[Paint::Stars as usize] = Color::TRANSPARENT;
+        theme.colors[Paint::SettingsBackground as usize] = self[Paint::SettingsBackground].with_alpha(1.0);
+        let on_walls = [Paint::WallLine, Paint::Racket, Paint::RacketBorder, Paint::Ball, Paint::BallLine];
+        let on_background = [Paint::Logo, Paint::Miss, Paint::MissHighlight, Paint::Text, Paint::Pause];
+        let on_settings = [Paint::SettingsText, Paint::SettingsSelected];
+        for (paints, against) in [
+            (&on_walls[..], Paint::Wall),
+            (&on_background[..], Paint::Background),
+            (&on_settings[..], Paint::SettingsBackground),
+        ] {
+            for &paint in paints {
+                theme.colors[paint as usize] = stand_out(self[paint], theme[against]);
+            }
+        }
+        theme
+    }
+}
+
+impl Index<Paint> for Theme {
+    type Output = Color;
+    fn index(&self,  paint: Paint) -> &Color {
+        &self.colors[paint as usize]
+    }
+}
+
+pub fn load_themes() -> Vec<Theme> {
+    THEMES.iter().map(|&(name, built_in)| Theme::load(name, built_in)).collect()
+}
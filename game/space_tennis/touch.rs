@@ -0,0 +1,150 @@
+//! Playing with a touch screen.
+
+use ::interface::game::TouchPhase;
+
+const TAP_MAX_DISTANCE: f32 = 0.03; // of the screen
+const TAP_MAX_DURATION: f32 = 0.3; // seconds
+
+/// What a touch event means for the game.
+#[derive(Clone,Copy, Debug, PartialEq)]
+pub enum TouchAction {
+    Nothing,
+    /// Move the racket this much, in fractions of the screen.
+    Drag([f32; 2]),
+    /// Serve or pause.
+    Tap,
+}
+
+#[derive(Clone,Copy, Debug)]
+struct Finger {
+    id: u64,
+    start_pos: [f32; 2],
+    last_pos: [f32; 2],
+    /// The furthest it has been from `start_pos`.
+    max_distance: f32,
+    /// Seconds since it touched the screen.
+    age: f32,
+}
+
+/// Turns touch events into racket movement and taps.
+///
+/// The first finger put down drags the racket, and another one takes over
+/// if it's still down when that finger is lifted.
+/// Any finger that is lifted quickly without moving much is a tap.
+#[derive(Clone, Default, Debug)]
+pub struct Touches {
+    fingers: Vec<Finger>,
+    dragging: Option<u64>,
+}
+
+impl Touches {
+    pub fn update(&mut self,  dt: f32) {
+        for finger in &mut self.fingers {
+            finger.age += dt;
+        }
+    }
+
+    pub fn touch(&mut self,  id: u64,  phase: TouchPhase,  pos: [f32; 2]) -> TouchAction {
+        let index = self.fingers.iter().position(|finger| finger.id == id);
+        match (phase, index) {
+            (TouchPhase::Start, _) => {
+                // in case the end of a previous touch with the same id was missed
+                self.fingers.retain(|finger| finger.id != id);
+                self.fingers.push(Finger {
+                    id,
+                    start_pos: pos,
+                    last_pos: pos,
+                    max_distance: 0.0,
+                    age: 0.0,
+                });
+                self.dragging = self.dragging.or(Some(id));
+                TouchAction::Nothing
+            }
+            (TouchPhase::Move, Some(index)) => {
+                let finger = &mut self.fingers[index];
+                let moved = [pos[0]-finger.last_pos[0], pos[1]-finger.last_pos[1]];
+                let distance = (pos[0]-finger.start_pos[0]).hypot(pos[1]-finger.start_pos[1]);
+                finger.max_distance = finger.max_distance.max(distance);
+                finger.last_pos = pos;
+                if self.dragging == Some(id) {
+                    TouchAction::Drag(moved)
+                } else {
+                    TouchAction::Nothing
+                }
+            }
+            (TouchPhase::End, Some(index)) | (TouchPhase::Cancel, Some(index)) => {
+                let finger = self.fingers.remove(index);
+                if self.dragging == Some(id) {
+                    self.dragging = self.fingers.first().map(|finger| finger.id);
+                }
+                if phase == TouchPhase::End
+                && finger.max_distance <= TAP_MAX_DISTANCE
+                && finger.age <= TAP_MAX_DURATION {
+                    TouchAction::Tap
+                } else {
+                    TouchAction::Nothing
+                }
+            }
+            (_, None) => TouchAction::Nothing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dragging() {
+        let mut touches = Touches::default();
+        assert_eq!(touches.touch(1, TouchPhase::Start, [0.5, 0.5]), TouchAction::Nothing);
+        assert_eq!(touches.touch(1, TouchPhase::Move, [0.75, 0.5]), TouchAction::Drag([0.25, 0.0]));
+        assert_eq!(touches.touch(1, TouchPhase::Move, [0.75, 0.25]), TouchAction::Drag([0.0, -0.25]));
+        // moved too far to be a tap
+        assert_eq!(touches.touch(1, TouchPhase::End, [0.75, 0.25]), TouchAction::Nothing);
+        assert_eq!(touches.touch(1, TouchPhase::Move, [0.5, 0.5]), TouchAction::Nothing);
+    }
+
+    #[test]
+    fn tapping() {
+        let mut touches = Touches::default();
+        touches.touch(1, TouchPhase::Start, [0.5, 0.5]);
+        touches.update(TAP_MAX_DURATION/2.0);
+        touches.touch(1, TouchPhase::Move, [0.5 + TAP_MAX_DISTANCE/2.0, 0.5]);
+        assert_eq!(touches.touch(1, TouchPhase::End, [0.5, 0.5]), TouchAction::Tap);
+    }
+
+    #[test]
+    fn not_tapping() {
+        let mut touches = Touches::default();
+        // moved too far, even though it came back
+        touches.touch(1, TouchPhase::Start, [0.5, 0.5]);
+        touches.touch(1, TouchPhase::Move, [0.5 + TAP_MAX_DISTANCE*2.0, 0.5]);
+        touches.touch(1, TouchPhase::Move, [0.5, 0.5]);
+        assert_eq!(touches.touch(1, TouchPhase::End, [0.5, 0.5]), TouchAction::Nothing);
+        // held too long
+        touches.touch(2, TouchPhase::Start, [0.5, 0.5]);
+        touches.update(TAP_MAX_DURATION*2.0);
+        assert_eq!(touches.touch(2, TouchPhase::End, [0.5, 0.5]), TouchAction::Nothing);
+        // interrupted
+        touches.touch(3, TouchPhase::Start, [0.5, 0.5]);
+        assert_eq!(touches.touch(3, TouchPhase::Cancel, [0.5, 0.5]), TouchAction::Nothing);
+        // never started
+        assert_eq!(touches.touch(4, TouchPhase::End, [0.5, 0.5]), TouchAction::Nothing);
+    }
+
+    #[test]
+    fn next_finger_takes_over_dragging() {
+        let mut touches = Touches::default();
+        touches.touch(1, TouchPhase::Start, [0.25, 0.5]);
+        touches.touch(2, TouchPhase::Start, [0.75, 0.5]);
+        assert_eq!(touches.touch(2, TouchPhase::Move, [0.75, 0.25]), TouchAction::Nothing);
+        assert_eq!(touches.touch(1, TouchPhase::Move, [0.25, 0.25]), TouchAction::Drag([0.0, -0.25]));
+        touches.touch(1, TouchPhase::End, [0.25, 0.25]);
+        assert_eq!(touches.touch(2, TouchPhase::Move, [0.5, 0.25]), TouchAction::Drag([-0.25, 0.0]));
+        // a finger put down after that doesn't take over
+        touches.touch(3, TouchPhase::Start, [0.5, 0.5]);
+        assert_eq!(touches.touch(3, TouchPhase::Move, [0.5, 0.75]), TouchAction::Nothing);
+        assert_eq!(touches.touch(2, TouchPhase::Move, [0.5, 0.5]), TouchAction::Drag([0.0, 0.25]));
+    }
+}
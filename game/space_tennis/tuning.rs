@@ -0,0 +1,211 @@
+//! The tuning file, and parsing the values in it and the settings file.
+
+use ::interface::camera::Camera;
+use std::fs;
+use std::io::ErrorKind;
+
+/// The file in the working directory that overrides the built-in tuning.
+pub const TUNING_FILE: &str = "space_tennis_tuning.txt";
+
+/// Values in the tuning and settings files.
+pub trait Setting: Sized {
+    fn parse(value: &str) -> Result<Self, String>;
+}
+
+impl Setting for f32 {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.parse::<f32>() {
+            Ok(number) if number.is_finite() => Ok(number),
+            _ => Err(format!("{:?} is not a number", value)),
+        }
+    }
+}
+
+impl Setting for u32 {
+    fn parse(value: &str) -> Result<Self, String> {
+        value.parse().map_err(|_| format!("{:?} is not a whole number", value))
+    }
+}
+
+impl Setting for bool {
+    fn parse(value: &str) -> Result<Self, String> {
+        value.parse().map_err(|_| format!("{:?} is not true or false", value))
+    }
+}
+
+/// Comma-separated numbers.
+pub fn parse_numbers<const N: usize>(value: &str) -> Result<[f32; N], String> {
+    let mut numbers = [0.0; N];
+    let mut parts = value.split(',');
+    for number in &mut numbers {
+        let part = parts.next().ok_or_else(|| format!("{:?} has less than {} numbers", value, N))?;
+        *number = f32::parse(part.trim())?;
+    }
+    match parts.next() {
+        Some(_) => Err(format!("{:?} has more than {} numbers", value, N)),
+        None => Ok(numbers),
+    }
+}
+
+impl Setting for [f32; 2] {
+    fn parse(value: &str) -> Result<Self, String> {
+        parse_numbers(value)
+    }
+}
+
+impl Setting for [f32; 3] {
+    fn parse(value: &str) -> Result<Self, String> {
+        parse_numbers(value)
+    }
+}
+
+/// Defines `Tuning` with a field for every setting in the tuning file.
+macro_rules! tuning {($($(#[$doc:meta])* $setting:ident: $type:ty,)*) => {
+    /// Values that decide how the game looks and feels,
+    /// which are read from `TUNING_FILE` and can be changed while the game is running.
+    #[derive(Clone,Copy, Debug, Default, PartialEq)]
+    pub struct Tuning {
+        $($(#[$doc])* pub $setting: $type,)*
+    }
+
+    impl Tuning {
+        const SETTINGS: &'static [&'static str] = &[$(stringify!($setting),)*];
+
+        fn set(&mut self,  setting: &str,  value: &str) -> Result<(), String> {
+            match setting {
+                $(stringify!($setting) => self.$setting = Setting::parse(value)?,)*
+                _ => return Err(format!("unknown setting {:?}", setting)),
+            }
+            self.check(setting)
+        }
+    }
+}}
+
+tuning!{
+    /// width, height, depth
+    arena: [f32; 3],
+    ball_radius: f32,
+    miss_ball_radius: f32,
+    racket_size: [f32; 2],
+    /// [left/right, top/bottom]
+    racket_border_width: [f32; 2],
+    player_max_speed: [f32; 2],
+    /// per second, when a direction is held
+    keyboard_acceleration: f32,
+    /// per second, when released or reversing
+    keyboard_deceleration: f32,
+    opponent_max_speed: [f32; 2],
+    /// of arena[2]
+    player_restart_delay: f32,
+    /// of arena[2]
+    opponent_restart_delay: f32,
+    ball_start_zspeed: f32,
+    ball_zspeed_level_add: f32,
+    /// based on mass of ball and bracket
+    bracket_speed_transfer: f32,
+    /// along the shorter side of the window
+    fov_degrees: f32,
+    /// of the screen
+    front_fills: f32,
+    wall_lines: u32,
+    line_width: f32,
+    /// a match is over when one side has missed this many times
+    match_misses: u32,
+}
+
+impl Tuning {
+    /// The values in the tuning file the game was compiled with.
+    pub fn built_in() -> Self {
+        let mut tuning = Tuning::default();
+        let missing = tuning.apply(include_str!("../../space_tennis_tuning.txt"))
+            .expect("parse built-in tuning");
+        assert!(missing.is_empty(), "{} is missing {:?}", TUNING_FILE, missing);
+        tuning
+    }
+
+    /// Change the settings that `contents` has values for,
+    /// returning the settings it doesn't have.
+    ///
+    /// Nothing is changed if there is an error.
+    pub fn apply(&mut self,  contents: &str) -> Result<Vec<&'static str>, String> {
+        let mut changed = *self;
+        let mut missing = Tuning::SETTINGS.to_vec();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (setting, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: missing =", i+1))?;
+            let setting = setting.trim();
+            changed.set(setting, value.trim()).map_err(|e| format!("line {}: {}", i+1, e))?;
+            missing.retain(|&unset| unset != setting);
+        }
+        *self = changed;
+        Ok(missing)
+    }
+
+    /// The built-in values with the ones in `TUNING_FILE`, if it exists.
+    pub fn load() -> Result<Self, String> {
+        let mut tuning = Tuning::built_in();
+        match fs::read_to_string(TUNING_FILE) {
+            Ok(contents) => {
+                tuning.apply(&contents)?;
+            }
+            Err(ref e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        Ok(tuning)
+    }
+
+    /// Reject values that would make the game divide by zero, overflow or turn inside out.
+    fn check(&self,  setting: &str) -> Result<(), String> {
+        let all = |values: &[f32],  valid: fn(f32) -> bool,  what: &str| {
+            match values.iter().all(|&value| valid(value)) {
+                true => Ok(()),
+                false => Err(format!("{} must be {}", setting, what)),
+            }
+        };
+        let positive = |values: &[f32]| all(values, |value| value > 0.0, "positive");
+        let not_negative = |values: &[f32]| all(values, |value| value >= 0.0, "zero or positive");
+        match setting {
+            "arena" => positive(&self.arena),
+            "ball_radius" => positive(&[self.ball_radius]),
+            "miss_ball_radius" => positive(&[self.miss_ball_radius]),
+            "racket_size" => positive(&self.racket_size),
+            "racket_border_width" => not_negative(&self.racket_border_width),
+            "player_max_speed" => not_negative(&self.player_max_speed),
+            "keyboard_acceleration" => not_negative(&[self.keyboard_acceleration]),
+            "keyboard_deceleration" => not_negative(&[self.keyboard_deceleration]),
+            "opponent_max_speed" => not_negative(&self.opponent_max_speed),
+            "player_restart_delay" => not_negative(&[self.player_restart_delay]),
+            "opponent_restart_delay" => not_negative(&[self.opponent_restart_delay]),
+            "ball_start_zspeed" => positive(&[self.ball_start_zspeed]),
+            "ball_zspeed_level_add" => not_negative(&[self.ball_zspeed_level_add]),
+            "bracket_speed_transfer" => not_negative(&[self.bracket_speed_transfer]),
+            "fov_degrees" => all(&[self.fov_degrees], |fov| fov > 0.0 && fov < 180.0, "between 0 and 180"),
+            "front_fills" => positive(&[self.front_fills]),
+            "wall_lines" if self.wall_lines > 100 => Err("wall_lines must be at most 100".to_string()),
+            "line_width" => not_negative(&[self.line_width]),
+            "match_misses" if self.match_misses == 0 => Err("match_misses must be at least 1".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// The lines at the ends of the arena are thicker.
+    pub fn line_width_edge(&self) -> f32 {
+        self.line_width*1.5
+    }
+
+    /// How many miss balls fit along the side of the arena.
+    pub fn max_misses(&self) -> u32 {
+        (self.arena[1]/(3.0*self.miss_ball_radius)) as u32
+    }
+
+    /// Place the camera so that the front of the arena fills `front_fills` of the screen.
+    pub fn place_camera(&self,  camera: &mut Camera) {
+        camera.fov = self.fov_degrees.to_radians();
+        let view_distance = camera.distance_to_fill(self.arena[0], self.front_fills);
+        camera.position = [self.arena[0]/2.0, self.arena[1]/2.0, -view_distance];
+    }
+}
@@ -0,0 +1,103 @@
+#!/usr/bin/env python3
+"""Draws the textures the game uses, without needing any image libraries.
+
+sprites.png is an atlas with the ball at [0, 0, 64, 64]
+and the logo at [0, 64, 216, 24].
+stars.png is the background.
+Run from the repository root.
+"""
+
+import math
+import random
+import struct
+import zlib
+
+
+def write_png(path, width, height, pixels):
+    """pixels is a list of rows of (r, g, b, a) tuples"""
+    def chunk(kind, data):
+        body = kind + data
+        return struct.pack('>I', len(data)) + body + struct.pack('>I', zlib.crc32(body))
+    raw = b''.join(b'\0' + bytes(c for pixel in row for c in pixel) for row in pixels)
+    with open(path, 'wb') as f:
+        f.write(b'\x89PNG\r\n\x1a\n')
+        f.write(chunk(b'IHDR', struct.pack('>IIBBBBB', width, height, 8, 6, 0, 0, 0)))
+        f.write(chunk(b'IDAT', zlib.compress(raw, 9)))
+        f.write(chunk(b'IEND', b''))
+
+
+def clamp(v):
+    return max(0, min(255, int(round(v))))
+
+
+def ball(x, y):
+    """grey with light seams and shading, to be tinted by the game"""
+    nx, ny = (x + 0.5 - 32) / 31.5, (y + 0.5 - 32) / 31.5
+    d = math.hypot(nx, ny)
+    if d > 1.0 + 1/31.5:
+        return (0, 0, 0, 0)
+    alpha = min(1.0, (1.0 + 1/31.5 - d) * 31.5)
+    nz = math.sqrt(max(0.0, 1.0 - d*d))
+    light = 0.55 + 0.45 * max(0.0, -0.4*nx - 0.5*ny + 0.77*nz)
+    # two seams curving around the ball
+    curve = 0.9 - 0.5*(1.0 - ny*ny)
+    seam = min(abs(nx - curve), abs(nx + curve))
+    if seam < 0.06:
+        light = 1.0
+    return (clamp(255*light), clamp(255*light), clamp(255*light), clamp(255*alpha))
+
+
+GLYPHS = {
+    'S': ['01111', '10000', '10000', '01110', '00001', '00001', '11110'],
+    'P': ['11110', '10001', '10001', '11110', '10000', '10000', '10000'],
+    'A': ['01110', '10001', '10001', '11111', '10001', '10001', '10001'],
+    'C': ['01111', '10000', '10000', '10000', '10000', '10000', '01111'],
+    'E': ['11111', '10000', '10000', '11110', '10000', '10000', '11111'],
+    'T': ['11111', '00100', '00100', '00100', '00100', '00100', '00100'],
+    'N': ['10001', '11001', '10101', '10011', '10001', '10001', '10001'],
+    'I': ['11111', '00100', '00100', '00100', '00100', '00100', '11111'],
+    ' ': ['00000'] * 7,
+}
+
+
+def logo_mask(text, scale):
+    columns = []
+    for i, c in enumerate(text):
+        if i > 0:
+            columns.append('0' * 7)
+        for x in range(5):
+            columns.append(''.join(GLYPHS[c][y][x] for y in range(7)))
+    return lambda x, y: (0 <= x // scale < len(columns) and 0 <= y // scale < 7
+                         and columns[x // scale][y // scale] == '1')
+
+
+def sprites():
+    width, height = 256, 128
+    pixels = [[(0, 0, 0, 0)] * width for _ in range(height)]
+    for y in range(64):
+        for x in range(64):
+            pixels[y][x] = ball(x, y)
+    mask = logo_mask('SPACE TENNIS', 3)
+    for y in range(24):
+        for x in range(216):
+            if mask(x, y):
+                g = 1.0 - 0.35 * y / 21
+                pixels[64+y][x] = (clamp(200*g), 255, clamp(140*g), 255)
+            elif mask(x - 2, y - 2):
+                pixels[64+y][x] = (0, 40, 0, 200)
+    write_png('images/sprites.png', width, height, pixels)
+
+
+def stars():
+    size = 256
+    random.seed(1)
+    pixels = [[(4, 4, 16, 255)] * size for _ in range(size)]
+    for _ in range(160):
+        x, y = random.randrange(size), random.randrange(size)
+        b = random.randrange(90, 256)
+        pixels[y][x] = (b, b, clamp(b*1.1), 255)
+    write_png('images/stars.png', size, size, pixels)
+
+
+sprites()
+stars()
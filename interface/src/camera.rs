@@ -0,0 +1,95 @@
+use std::f32::consts::PI;
+
+/// A perspective camera looking along the positive Z axis.
+///
+/// World coordinates have X to the right and Y downwards like the screen,
+/// and the camera is in the center of the screen coordinate system,
+/// at `[0.5, 0.5]`.
+#[derive(Clone,Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// The field of view along the shorter side of the window, in radians.
+    pub fov: f32,
+    /// Where the camera is in the world.
+    pub position: [f32; 3],
+    /// The width of the window divided by its height.
+    pub aspect_ratio: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera { fov: PI/3.0,  position: [0.0, 0.0, 0.0],  aspect_ratio: 1.0 }
+    }
+}
+
+impl Camera {
+    pub fn new(fov: f32,  position: [f32; 3],  aspect_ratio: f32) -> Self {
+        Camera { fov, position, aspect_ratio }
+    }
+
+    /// Set `aspect_ratio` from a window size.
+    pub fn resize(&mut self,  window_size: [f32; 2]) {
+        self.aspect_ratio = window_size[0] / window_size[1];
+    }
+
+    /// The part of the screen coordinate system that is inside the window,
+    /// as `[left, top, width, height]`.
+    ///
+    /// Engines scale 0..1 to the shorter side, so the longer side sees more.
+    pub fn visible_area(&self) -> [f32; 4] {
+        let size = if self.aspect_ratio >= 1.0 {
+            [self.aspect_ratio, 1.0]
+        } else {
+            [1.0, self.aspect_ratio.recip()]
+        };
+        [0.5-size[0]/2.0, 0.5-size[1]/2.0, size[0], size[1]]
+    }
+
+    /// How far in front of the camera something is.
+    ///
+    /// Things with a depth of zero or less cannot be projected.
+    pub fn depth(&self,  point: [f32; 3]) -> f32 {
+        point[2] - self.position[2]
+    }
+
+    /// The length in screen coordinates of one unit at `depth`.
+    ///
+    /// At depth x the distance from top to bottom of the shorter side of the
+    /// view is 2*x*tan(fov/2), and that is 1 in screen coordinates.
+    pub fn scale(&self,  depth: f32) -> f32 {
+        1.0 / (2.0*depth*f32::tan(self.fov/2.0))
+    }
+
+    /// Convert a size at `depth` to screen coordinates.
+    pub fn project_size(&self,  size: f32,  depth: f32) -> f32 {
+        size * self.scale(depth)
+    }
+
+    /// Find where on the screen a point in the world is.
+    pub fn project(&self,  point: [f32; 3]) -> [f32; 2] {
+        let scale = self.scale(self.depth(point));
+        [
+            0.5 + (point[0]-self.position[0])*scale,
+            0.5 + (point[1]-self.position[1])*scale,
+        ]
+    }
+
+    /// Find the point in the world that is at `pos` on the screen
+    /// and has the Z coordinate `z`.
+    ///
+    /// This is the opposite of `project()`, and can be used to find where
+    /// the mouse points on a plane facing the camera.
+    pub fn unproject(&self,  pos: [f32; 2],  z: f32) -> [f32; 3] {
+        let scale = self.scale(z - self.position[2]);
+        [
+            self.position[0] + (pos[0]-0.5)/scale,
+            self.position[1] + (pos[1]-0.5)/scale,
+            z,
+        ]
+    }
+
+    /// The distance at which something `size` large fills `fraction`
+    /// of the shorter side of the window.
+    pub fn distance_to_fill(&self,  size: f32,  fraction: f32) -> f32 {
+        size / (2.0*fraction*f32::tan(self.fov/2.0))
+    }
+}
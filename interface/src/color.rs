@@ -0,0 +1,347 @@
+//! RGBA colors, and parsing them from the formats CSS uses.
+//!
+//! `Color::parse()` accepts
+//! * hex `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`, where the `#` is optional for the longer two,
+//! * CSS named colors like `cornflowerblue` or `transparent`,
+//! * `hsl(120, 100%, 50%)`, or `hsl(120deg 100% 50% / 0.5)` with alpha,
+//!   which can also be written `hsla()`.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A color with red, green, blue and alpha from 0.0 to 1.0, not premultiplied.
+///
+/// It has the same layout as `[f32; 4]`, which is what piston uses for colors,
+/// and converts to and from that.
+#[repr(C)]
+#[derive(Clone,Copy, Debug, Default, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+
+    pub const fn rgba(r: f32,  g: f32,  b: f32,  a: f32) -> Self {
+        Color { r, g, b, a }
+    }
+
+    pub const fn rgb(r: f32,  g: f32,  b: f32) -> Self {
+        Color { r,  g,  b,  a: 1.0 }
+    }
+
+    /// From 0xRRGGBBAA.
+    pub const fn from_u32(rgba: u32) -> Self {
+        let [r, g, b, a] = rgba.to_be_bytes();
+        Color::rgba(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0)
+    }
+
+    /// `hue` is in degrees, and the others are from 0.0 to 1.0.
+    pub fn hsla(hue: f32,  saturation: f32,  lightness: f32,  alpha: f32) -> Self {
+        // the conversion from the CSS specification
+        let hue = hue.rem_euclid(360.0);
+        let (saturation, lightness) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+        let chroma = saturation * lightness.min(1.0-lightness);
+        let channel = |n: f32| {
+            let k = (n + hue/30.0) % 12.0;
+            lightness - chroma * (k-3.0).min(9.0-k).clamp(-1.0, 1.0)
+        };
+        Color::rgba(channel(0.0), channel(8.0), channel(4.0), alpha.clamp(0.0, 1.0))
+    }
+
+    pub const fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub const fn with_alpha(self,  a: f32) -> Self {
+        Color { a, ..self }
+    }
+
+    /// Parse any of the formats described in the module documentation.
+    pub fn parse(color: &str) -> Result<Self, String> {
+        let color = color.trim();
+        let lowercase = color.to_ascii_lowercase();
+        if let Some(arguments) = lowercase.strip_prefix("hsla(").or_else(|| lowercase.strip_prefix("hsl(")) {
+            let arguments = arguments.strip_suffix(')')
+                .ok_or_else(|| format!("{:?} lacks the closing )", color))?;
+            return parse_hsl(arguments).map_err(|e| format!("{:?} is not a valid hsl(): {}", color, e));
+        }
+        if let Ok(i) = NAMED_COLORS.binary_search_by(|&(name, _)| name.cmp(&lowercase)) {
+            return Ok(Color::from_u32(NAMED_COLORS[i].1));
+        }
+        let (digits, prefixed) = match color.strip_prefix('#') {
+            Some(digits) => (digits, true),
+            None => (color, false),
+        };
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("{:?} is not a color", color));
+        }
+        let value = u32::from_str_radix(digits, 16).unwrap_or(0);
+        // repeat each digit of the short forms
+        let double = |short: u32| (0..4).rev().fold(0, |long, i| (long << 8) | (((short >> (4*i)) & 0xf) * 0x11));
+        match (digits.len(), prefixed) {
+            (3, true) => Ok(Color::from_u32(double(value << 4 | 0xf))),
+            (4, true) => Ok(Color::from_u32(double(value))),
+            (6, _) => Ok(Color::from_u32(value << 8 | 0xff)),
+            (8, _) => Ok(Color::from_u32(value)),
+            (3, false) | (4, false) => Err(format!("{:?} needs a # before short hex colors", color)),
+            _ => Err(format!("{:?} doesn't have 3, 4, 6 or 8 hex digits", color)),
+        }
+    }
+
+    /// Blend linearly, so that `t` = 0.0 gives `self` and 1.0 gives `other`.
+    pub fn lerp(self,  other: Color,  t: f32) -> Self {
+        Color::rgba(
+            self.r + (other.r-self.r)*t,
+            self.g + (other.g-self.g)*t,
+            self.b + (other.b-self.b)*t,
+            self.a + (other.a-self.a)*t,
+        )
+    }
+
+    /// Multiply the color by its alpha, as some blend modes want.
+    pub fn premultiply(self) -> Self {
+        Color::rgba(self.r*self.a, self.g*self.a, self.b*self.a, self.a)
+    }
+
+    /// Mix `amount` of white into the color, keeping its alpha.
+    pub fn lighten(self,  amount: f32) -> Self {
+        self.lerp(Color::WHITE.with_alpha(self.a), amount)
+    }
+
+    /// Mix `amount` of black into the color, keeping its alpha.
+    pub fn darken(self,  amount: f32) -> Self {
+        self.lerp(Color::BLACK.with_alpha(self.a), amount)
+    }
+
+    /// How bright the color looks, from 0.0 for black to 1.0 for white, ignoring alpha.
+    ///
+    /// As defined by WCAG for sRGB colors.
+    pub fn relative_luminance(self) -> f32 {
+        let linear = |c: f32| {
+            if c <= 0.04045 {c / 12.92} else {((c + 0.055) / 1.055).powf(2.4)}
+        };
+        0.2126*linear(self.r) + 0.7152*linear(self.g) + 0.0722*linear(self.b)
+    }
+
+    /// From 1.0 for identical colors to 21.0 for black and white, ignoring alpha.
+    ///
+    /// WCAG recommends at least 4.5 for text.
+    pub fn contrast_ratio(self,  other: Color) -> f32 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Color { r, g, b, a }
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        color.to_array()
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+    fn from_str(color: &str) -> Result<Self, String> {
+        Color::parse(color)
+    }
+}
+
+/// As `#rrggbbaa`.
+impl Display for Color {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        let byte = |c: f32| (c.clamp(0.0, 1.0)*255.0).round() as u8;
+        write!(fmtr, "#{:02x}{:02x}{:02x}{:02x}", byte(self.r), byte(self.g), byte(self.b), byte(self.a))
+    }
+}
+
+/// The arguments of `hsl()` without the parentheses.
+fn parse_hsl(arguments: &str) -> Result<Color, String> {
+    let (arguments, alpha) = match arguments.split_once('/') {
+        Some((arguments, alpha)) => (arguments, Some(alpha.trim())),
+        None => (arguments, None),
+    };
+    let mut parts = arguments.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    let alpha = match (alpha, parts.len()) {
+        (Some(alpha), 3) => alpha,
+        (None, 4) => parts.pop().unwrap(),
+        (None, 3) => "1",
+        _ => return Err("needs hue, saturation, lightness and optionally alpha".to_string()),
+    };
+    let number = |part: &str| match part.parse::<f32>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(format!("{:?} is not a number", part)),
+    };
+    let percent = |part: &str| match part.strip_suffix('%') {
+        Some(percent) => number(percent).map(|percent| percent/100.0),
+        None => Err(format!("{:?} is not a percentage", part)),
+    };
+    let hue = number(parts[0].strip_suffix("deg").unwrap_or(parts[0]))?;
+    let alpha = if alpha.ends_with('%') {percent(alpha)?} else {number(alpha)?};
+    Ok(Color::hsla(hue, percent(parts[1])?, percent(parts[2])?, alpha))
+}
+
+/// From the CSS specification, sorted by name.
+const NAMED_COLORS: [(&str, u32); 149] = [
+    ("aliceblue", 0xf0f8ffff),
+    ("antiquewhite", 0xfaebd7ff),
+    ("aqua", 0x00ffffff),
+    ("aquamarine", 0x7fffd4ff),
+    ("azure", 0xf0ffffff),
+    ("beige", 0xf5f5dcff),
+    ("bisque", 0xffe4c4ff),
+    ("black", 0x000000ff),
+    ("blanchedalmond", 0xffebcdff),
+    ("blue", 0x0000ffff),
+    ("blueviolet", 0x8a2be2ff),
+    ("brown", 0xa52a2aff),
+    ("burlywood", 0xdeb887ff),
+    ("cadetblue", 0x5f9ea0ff),
+    ("chartreuse", 0x7fff00ff),
+    ("chocolate", 0xd2691eff),
+    ("coral", 0xff7f50ff),
+    ("cornflowerblue", 0x6495edff),
+    ("cornsilk", 0xfff8dcff),
+    ("crimson", 0xdc143cff),
+    ("cyan", 0x00ffffff),
+    ("darkblue", 0x00008bff),
+    ("darkcyan", 0x008b8bff),
+    ("darkgoldenrod", 0xb8860bff),
+    ("darkgray", 0xa9a9a9ff),
+    ("darkgreen", 0x006400ff),
+    ("darkgrey", 0xa9a9a9ff),
+    ("darkkhaki", 0xbdb76bff),
+    ("darkmagenta", 0x8b008bff),
+    ("darkolivegreen", 0x556b2fff),
+    ("darkorange", 0xff8c00ff),
+    ("darkorchid", 0x9932ccff),
+    ("darkred", 0x8b0000ff),
+    ("darksalmon", 0xe9967aff),
+    ("darkseagreen", 0x8fbc8fff),
+    ("darkslateblue", 0x483d8bff),
+    ("darkslategray", 0x2f4f4fff),
+    ("darkslategrey", 0x2f4f4fff),
+    ("darkturquoise", 0x00ced1ff),
+    ("darkviolet", 0x9400d3ff),
+    ("deeppink", 0xff1493ff),
+    ("deepskyblue", 0x00bfffff),
+    ("dimgray", 0x696969ff),
+    ("dimgrey", 0x696969ff),
+    ("dodgerblue", 0x1e90ffff),
+    ("firebrick", 0xb22222ff),
+    ("floralwhite", 0xfffaf0ff),
+    ("forestgreen", 0x228b22ff),
+    ("fuchsia", 0xff00ffff),
+    ("gainsboro", 0xdcdcdcff),
+    ("ghostwhite", 0xf8f8ffff),
+    ("gold", 0xffd700ff),
+    ("goldenrod", 0xdaa520ff),
+    ("gray", 0x808080ff),
+    ("green", 0x008000ff),
+    ("greenyellow", 0xadff2fff),
+    ("grey", 0x808080ff),
+    ("honeydew", 0xf0fff0ff),
+    ("hotpink", 0xff69b4ff),
+    ("indianred", 0xcd5c5cff),
+    ("indigo", 0x4b0082ff),
+    ("ivory", 0xfffff0ff),
+    ("khaki", 0xf0e68cff),
+    ("lavender", 0xe6e6faff),
+    ("lavenderblush", 0xfff0f5ff),
+    ("lawngreen", 0x7cfc00ff),
+    ("lemonchiffon", 0xfffacdff),
+    ("lightblue", 0xadd8e6ff),
+    ("lightcoral", 0xf08080ff),
+    ("lightcyan", 0xe0ffffff),
+    ("lightgoldenrodyellow", 0xfafad2ff),
+    ("lightgray", 0xd3d3d3ff),
+    ("lightgreen", 0x90ee90ff),
+    ("lightgrey", 0xd3d3d3ff),
+    ("lightpink", 0xffb6c1ff),
+    ("lightsalmon", 0xffa07aff),
+    ("lightseagreen", 0x20b2aaff),
+    ("lightskyblue", 0x87cefaff),
+    ("lightslategray", 0x778899ff),
+    ("lightslategrey", 0x778899ff),
+    ("lightsteelblue", 0xb0c4deff),
+    ("lightyellow", 0xffffe0ff),
+    ("lime", 0x00ff00ff),
+    ("limegreen", 0x32cd32ff),
+    ("linen", 0xfaf0e6ff),
+    ("magenta", 0xff00ffff),
+    ("maroon", 0x800000ff),
+    ("mediumaquamarine", 0x66cdaaff),
+    ("mediumblue", 0x0000cdff),
+    ("mediumorchid", 0xba55d3ff),
+    ("mediumpurple", 0x9370dbff),
+    ("mediumseagreen", 0x3cb371ff),
+    ("mediumslateblue", 0x7b68eeff),
+    ("mediumspringgreen", 0x00fa9aff),
+    ("mediumturquoise", 0x48d1ccff),
+    ("mediumvioletred", 0xc71585ff),
+    ("midnightblue", 0x191970ff),
+    ("mintcream", 0xf5fffaff),
+    ("mistyrose", 0xffe4e1ff),
+    ("moccasin", 0xffe4b5ff),
+    ("navajowhite", 0xffdeadff),
+    ("navy", 0x000080ff),
+    ("oldlace", 0xfdf5e6ff),
+    ("olive", 0x808000ff),
+    ("olivedrab", 0x6b8e23ff),
+    ("orange", 0xffa500ff),
+    ("orangered", 0xff4500ff),
+    ("orchid", 0xda70d6ff),
+    ("palegoldenrod", 0xeee8aaff),
+    ("palegreen", 0x98fb98ff),
+    ("paleturquoise", 0xafeeeeff),
+    ("palevioletred", 0xdb7093ff),
+    ("papayawhip", 0xffefd5ff),
+    ("peachpuff", 0xffdab9ff),
+    ("peru", 0xcd853fff),
+    ("pink", 0xffc0cbff),
+    ("plum", 0xdda0ddff),
+    ("powderblue", 0xb0e0e6ff),
+    ("purple", 0x800080ff),
+    ("rebeccapurple", 0x663399ff),
+    ("red", 0xff0000ff),
+    ("rosybrown", 0xbc8f8fff),
+    ("royalblue", 0x4169e1ff),
+    ("saddlebrown", 0x8b4513ff),
+    ("salmon", 0xfa8072ff),
+    ("sandybrown", 0xf4a460ff),
+    ("seagreen", 0x2e8b57ff),
+    ("seashell", 0xfff5eeff),
+    ("sienna", 0xa0522dff),
+    ("silver", 0xc0c0c0ff),
+    ("skyblue", 0x87ceebff),
+    ("slateblue", 0x6a5acdff),
+    ("slategray", 0x708090ff),
+    ("slategrey", 0x708090ff),
+    ("snow", 0xfffafaff),
+    ("springgreen", 0x00ff7fff),
+    ("steelblue", 0x4682b4ff),
+    ("tan", 0xd2b48cff),
+    ("teal", 0x008080ff),
+    ("thistle", 0xd8bfd8ff),
+    ("tomato", 0xff6347ff),
+    ("transparent", 0x00000000),
+    ("turquoise", 0x40e0d0ff),
+    ("violet", 0xee82eeff),
+    ("wheat", 0xf5deb3ff),
+    ("white", 0xffffffff),
+    ("whitesmoke", 0xf5f5f5ff),
+    ("yellow", 0xffff00ff),
+    ("yellowgreen", 0x9acd32ff),
+];
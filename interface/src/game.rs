@@ -1,12 +1,45 @@
-use std::borrow::Cow;
+use crate::camera::Camera;
+pub use crate::color::Color;
+use crate::text::{FallbackMetrics, MeasureText, TextMetrics};
+use crate::transform::Transform;
 
-/// Matches `piston_window::types::Color`
-pub type Color = [f32; 4];
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::f32::consts::PI;
 
 #[derive(Clone,Copy, Debug)]
 #[repr(i8)]
 pub enum Align {Left=-1, Center=0, Right=1}
 
+/// Where text is vertically, relative to its position.
+#[derive(Clone,Copy, Debug)]
+pub enum VAlign {
+    Top,
+    Center,
+    Bottom,
+    /// The line that the letters of the first line stand on.
+    Baseline,
+}
+
+/// How to lay out the text of `Shape::StaticTextBox` and `Shape::DynamicTextBox`.
+#[derive(Clone,Copy, Debug)]
+pub struct TextBox {
+    /// Lines are wrapped between words to not get wider than this.
+    pub max_width: f32,
+    /// The distance between lines, relative to the default.
+    pub line_spacing: f32,
+    /// Both where each line is within `max_width`,
+    /// and where the box is relative to the text's position.
+    pub align: Align,
+    pub valign: VAlign,
+}
+
+impl TextBox {
+    pub fn new(max_width: f32,  align: Align,  valign: VAlign) -> Self {
+        TextBox { max_width,  line_spacing: 1.0,  align,  valign }
+    }
+}
+
 /// An element to render.
 #[derive(Clone, Debug)]
 pub enum Shape {
@@ -19,70 +52,624 @@ pub enum Shape {
     /// and `[area[2], area[3]]` is the size.
     Rectangle{ color: Color,  area: [f32;4] },
     Circle{ color: Color,  center: [f32; 2],  radius: f32 },
+    /// A filled convex polygon.
+    Polygon{ color: Color,  points: Vec<[f32; 2]> },
+    /// Triangles that all share the first point,
+    /// and where each other point is shared with the next triangle.
+    ///
+    /// Unlike `Polygon` this can be concave, as long as every edge can be
+    /// seen from the first point.
+    TriangleFan{ color: Color,  points: Vec<[f32; 2]> },
+    /// A filled ellipse that touches the edges of `area`,
+    /// which is a rectangle like for `Rectangle`.
+    Ellipse{ color: Color,  area: [f32;4] },
+    /// A filled rectangle with corners that are quarter circles.
+    RoundedRectangle{ color: Color,  area: [f32;4],  radius: f32 },
+    /// A part of a circle's edge, `width` thick inwards from `radius`.
+    ///
+    /// `angles` are the start and end in radians, where 0 is to the right,
+    /// and larger angles go clockwise because Y points downwards.
+    Arc{ color: Color,  width: f32,  center: [f32; 2],  radius: f32,  angles: [f32; 2] },
+    /// The edge of a shape, `width` thick inwards from the edge.
+    Stroke{ color: Color,  width: f32,  outline: Outline },
+    /// A texture, or a part of it, stretched to fill `area`.
+    ///
+    /// The texture's colors are multiplied by `tint`,
+    /// so white shows it as it is and the alpha of `tint` fades it.
+    Image{ tint: Color,  sprite: Sprite,  area: [f32;4] },
+    /// An image stretched to fit four corners, in clockwise order from the top left.
+    ImageQuad{ tint: Color,  sprite: Sprite,  corners: [[f32; 2]; 4] },
+    /// A filled convex polygon where the color changes across it.
+    Gradient{ gradient: Gradient,  points: Vec<[f32; 2]> },
+    /// Triangles with a color for each corner, which blend in between.
+    /// Every three vertices is a triangle.
+    Triangles{ vertices: Vec<([f32; 2], Color)> },
     StaticText {
         color: Color,
         position: [f32; 2],
-        center: [Align; 2],
+        center: (Align, VAlign),
         size: f32,
         text: &'static str,
     },
     DynamicText {
         color: Color,
         position: [f32; 2],
-        center: [Align; 2],
+        center: (Align, VAlign),
         size: f32,
         text: String,
     },
+    /// Text that can be more than one line.
+    /// Newlines start new lines, and long lines are wrapped.
+    StaticTextBox {
+        color: Color,
+        position: [f32; 2],
+        layout: TextBox,
+        size: f32,
+        text: &'static str,
+    },
+    DynamicTextBox {
+        color: Color,
+        position: [f32; 2],
+        layout: TextBox,
+        size: f32,
+        text: String,
+    },
+}
+
+/// An image file embedded in the game, such as with `include_bytes!()`.
+///
+/// Engines decode it the first time it's drawn and keep it by `name`,
+/// so different textures need different names.
+/// Which formats are supported depends on the engine, but PNG always is.
+#[derive(Clone,Copy, Debug)]
+pub struct Texture {
+    pub name: &'static str,
+    pub data: &'static [u8],
+}
+
+/// A rectangle of a texture, which can be a texture atlas with many sprites.
+#[derive(Clone,Copy, Debug)]
+pub struct Sprite {
+    pub texture: Texture,
+    /// `[left, top, width, height]` in pixels, or `None` for the whole texture.
+    pub source: Option<[f32;4]>,
+}
+
+impl Texture {
+    /// The whole texture.
+    pub const fn sprite(self) -> Sprite {
+        Sprite { texture: self,  source: None }
+    }
+
+    /// A part of the texture, in pixels.
+    pub const fn sprite_at(self,  source: [f32;4]) -> Sprite {
+        Sprite { texture: self,  source: Some(source) }
+    }
+}
+
+/// The shape whose edge `Shape::Stroke` draws.
+#[derive(Clone, Debug)]
+pub enum Outline {
+    Rectangle{ area: [f32;4] },
+    RoundedRectangle{ area: [f32;4],  radius: f32 },
+    Ellipse{ area: [f32;4] },
+    /// A convex polygon.
+    Polygon{ points: Vec<[f32; 2]> },
+}
+
+/// How many straight lines a full circle is approximated with.
+const CIRCLE_SEGMENTS: usize = 64;
+
+/// Points on the edge of an ellipse, from angle `angles[0]` to `angles[1]`.
+fn ellipse_points(center: [f32; 2],  radii: [f32; 2],  angles: [f32; 2]) -> Vec<[f32; 2]> {
+    let sweep = angles[1] - angles[0];
+    let segments = (sweep.abs() / (2.0*PI) * CIRCLE_SEGMENTS as f32).ceil().max(1.0) as usize;
+    (0..=segments).map(|i| {
+        let angle = angles[0] + sweep * i as f32 / segments as f32;
+        [center[0] + radii[0]*angle.cos(),  center[1] + radii[1]*angle.sin()]
+    }).collect()
+}
+
+/// Quads that fill the space between two lines with the same number of points.
+fn quads_between(outer: &[[f32; 2]],  inner: &[[f32; 2]]) -> Vec<[[f32; 2]; 4]> {
+    outer.windows(2).zip(inner.windows(2))
+        .map(|(outer, inner)| [outer[0], outer[1], inner[1], inner[0]])
+        .collect()
+}
+
+impl Outline {
+    /// Points around the outline after moving it `inset` inwards.
+    ///
+    /// The first point is repeated at the end,
+    /// and the number of points doesn't depend on `inset`.
+    pub fn points(&self,  inset: f32) -> Vec<[f32; 2]> {
+        // don't let the sides cross each other
+        let shrink = |[x, y, w, h]: [f32; 4]| {
+            let inset = inset.min(w/2.0).min(h/2.0);
+            [x+inset, y+inset, w-2.0*inset, h-2.0*inset]
+        };
+        let mut points = match self {
+            Outline::Rectangle{ area } => {
+                let [x, y, w, h] = shrink(*area);
+                vec![[x, y], [x+w, y], [x+w, y+h], [x, y+h]]
+            }
+            Outline::RoundedRectangle{ area, radius } => {
+                let [x, y, w, h] = shrink(*area);
+                let radius = (radius - inset).max(0.0).min(w/2.0).min(h/2.0);
+                let corners = [
+                    ([x+w-radius, y+h-radius], 0.0),
+                    ([x+radius, y+h-radius], 0.5*PI),
+                    ([x+radius, y+radius], PI),
+                    ([x+w-radius, y+radius], 1.5*PI),
+                ];
+                corners.iter()
+                    .flat_map(|&(center, start)| {
+                        ellipse_points(center, [radius, radius], [start, start+0.5*PI])
+                    })
+                    .collect()
+            }
+            Outline::Ellipse{ area } => {
+                let [x, y, w, h] = shrink(*area);
+                let mut points = ellipse_points([x+w/2.0, y+h/2.0], [w/2.0, h/2.0], [0.0, 2.0*PI]);
+                points.pop();
+                points
+            }
+            Outline::Polygon{ points } => {
+                // twice the signed area, to know which side is inside
+                let n = points.len();
+                let area = (0..n).map(|i| {
+                    let ([x1, y1], [x2, y2]) = (points[i], points[(i+1)%n]);
+                    x1*y2 - x2*y1
+                }).sum::<f32>();
+                let inwards = |[x1, y1]: [f32; 2],  [x2, y2]: [f32; 2]| {
+                    let [dx, dy] = [x2-x1, y2-y1];
+                    let length = f32::hypot(dx, dy).max(f32::EPSILON);
+                    [-dy/length*area.signum(), dx/length*area.signum()]
+                };
+                (0..n).map(|i| {
+                    let (prev, point, next) = (points[(i+n-1)%n], points[i], points[(i+1)%n]);
+                    let (a, b) = (inwards(prev, point), inwards(point, next));
+                    // move along the sum of the edge normals until both edges have moved by `inset`
+                    let scale = inset / (1.0 + a[0]*b[0] + a[1]*b[1]).max(0.1);
+                    [point[0] + (a[0]+b[0])*scale,  point[1] + (a[1]+b[1])*scale]
+                }).collect()
+            }
+        };
+        if let Some(&first) = points.first() {
+            points.push(first);
+        }
+        points
+    }
+
+    /// Quads that together cover the edge, `width` thick inwards.
+    pub fn stroke(&self,  width: f32) -> Vec<[[f32; 2]; 4]> {
+        quads_between(&self.points(0.0), &self.points(width))
+    }
+}
+
+/// Quads that together cover `Shape::Arc`.
+pub fn arc_quads(width: f32,  center: [f32; 2],  radius: f32,  angles: [f32; 2]) -> Vec<[[f32; 2]; 4]> {
+    let inner = (radius - width).max(0.0);
+    quads_between(
+        &ellipse_points(center, [radius, radius], angles),
+        &ellipse_points(center, [inner, inner], angles),
+    )
+}
+
+/// Colors that change smoothly from one position to another.
+#[derive(Clone,Copy, Debug)]
+pub enum Gradient {
+    /// `colors[0]` at `from` and before it, `colors[1]` at `to` and after it,
+    /// which is everywhere if they are the same.
+    Linear{ from: [f32; 2],  to: [f32; 2],  colors: [Color; 2] },
+    /// `colors[0]` at `center`, and `colors[1]` at `radius` and further out,
+    /// which is everywhere if `radius` is zero.
+    Radial{ center: [f32; 2],  radius: f32,  colors: [Color; 2] },
+}
+
+/// How many pieces the gradient is split into along its length when drawn,
+/// since colors are only blended linearly between the corners of triangles.
+const GRADIENT_STEPS: f32 = 4.0;
+
+impl Gradient {
+    pub fn color_at(&self,  point: [f32; 2]) -> Color {
+        match *self {
+            Gradient::Linear{ from, to, colors } => {
+                let along = [to[0]-from[0], to[1]-from[1]];
+                let length_squared = along[0]*along[0] + along[1]*along[1];
+                if length_squared <= 0.0 {
+                    return colors[1];
+                }
+                let t = ((point[0]-from[0])*along[0] + (point[1]-from[1])*along[1]) / length_squared;
+                colors[0].lerp(colors[1], t.clamp(0.0, 1.0))
+            }
+            Gradient::Radial{ center, radius, colors } => {
+                if radius <= 0.0 {
+                    return colors[1];
+                }
+                let distance = f32::hypot(point[0]-center[0], point[1]-center[1]);
+                colors[0].lerp(colors[1], (distance/radius).clamp(0.0, 1.0))
+            }
+        }
+    }
+
+    /// Split a convex polygon into colored triangles small enough that
+    /// blending between the corners looks like the gradient.
+    ///
+    /// The result can be drawn like `Shape::Triangles`.
+    pub fn triangles(&self,  points: &[[f32; 2]]) -> Vec<([f32; 2], Color)> {
+        let mut vertices = Vec::new();
+        if points.len() < 3 {
+            return vertices;
+        }
+        let step = match *self {
+            Gradient::Linear{ from, to, .. } => f32::hypot(to[0]-from[0], to[1]-from[1]),
+            Gradient::Radial{ radius, .. } => radius,
+        } / GRADIENT_STEPS;
+        let center = [0, 1].map(|axis| {
+            points.iter().map(|point| point[axis]).sum::<f32>() / points.len() as f32
+        });
+        for (k, &a) in points.iter().enumerate() {
+            let b = points[(k+1) % points.len()];
+            let longest = [a, b].iter()
+                .map(|p| f32::hypot(p[0]-center[0], p[1]-center[1]))
+                .fold(f32::hypot(b[0]-a[0], b[1]-a[1]), f32::max);
+            // a single color doesn't need to be split up
+            let n = if step > 0.0 {(longest/step).ceil().clamp(1.0, 8.0) as usize} else {1};
+            let mut vertex = |i: usize,  j: usize| {
+                // barycentric coordinates in steps of 1/n
+                let (u, v) = (i as f32 / n as f32, j as f32 / n as f32);
+                let point = [0, 1].map(|axis| {
+                    center[axis] + (a[axis]-center[axis])*u + (b[axis]-center[axis])*v
+                });
+                vertices.push((point, self.color_at(point)));
+            };
+            for i in 0..n {
+                for j in 0..n-i {
+                    vertex(i, j);
+                    vertex(i+1, j);
+                    vertex(i, j+1);
+                    if i+j+1 < n {
+                        vertex(i+1, j);
+                        vertex(i+1, j+1);
+                        vertex(i, j+1);
+                    }
+                }
+            }
+        }
+        vertices
+    }
+}
+
+/// An element in world coordinates, which is projected to `Shape`s by a `Camera`.
+#[derive(Clone, Debug)]
+pub enum WorldShape {
+    Sphere{ color: Color,  center: [f32; 3],  radius: f32 },
+    /// A flat convex four-sided shape, with the corners in order around the edge.
+    Quad{ color: Color,  corners: [[f32; 3]; 4] },
+    /// A `Quad` with a color for each corner, which blend in between.
+    ShadedQuad{ colors: [Color; 4],  corners: [[f32; 3]; 4] },
+    /// `radius` is half the thickness, like `width` for `Shape::Line`.
+    Line{ color: Color,  radius: f32,  ends: [[f32; 3]; 2] },
+    /// A sprite that always faces the camera.
+    Billboard{ tint: Color,  sprite: Sprite,  center: [f32; 3],  size: [f32; 2] },
+}
+
+impl WorldShape {
+    /// The depth of the point farthest from the camera.
+    ///
+    /// Drawing farthest first by this works for shapes that don't intersect,
+    /// and for surfaces that the other shapes are inside of, such as walls.
+    fn farthest(&self,  camera: &Camera) -> f32 {
+        match self {
+            WorldShape::Sphere{ center, .. } => camera.depth(*center),
+            WorldShape::Billboard{ center, .. } => camera.depth(*center),
+            WorldShape::Quad{ corners, .. } | WorldShape::ShadedQuad{ corners, .. } => corners.iter()
+                .map(|&corner| camera.depth(corner))
+                .fold(f32::NEG_INFINITY, f32::max),
+            WorldShape::Line{ ends, .. } => f32::max(camera.depth(ends[0]), camera.depth(ends[1])),
+        }
+    }
+
+    /// The depth of the point closest to the camera.
+    fn nearest(&self,  camera: &Camera) -> f32 {
+        match self {
+            WorldShape::Sphere{ center, radius, .. } => camera.depth(*center) - radius,
+            WorldShape::Billboard{ center, .. } => camera.depth(*center),
+            WorldShape::Quad{ corners, .. } | WorldShape::ShadedQuad{ corners, .. } => corners.iter()
+                .map(|&corner| camera.depth(corner))
+                .fold(f32::INFINITY, f32::min),
+            WorldShape::Line{ ends, .. } => f32::min(camera.depth(ends[0]), camera.depth(ends[1])),
+        }
+    }
+
+    fn project(self,  camera: &Camera) -> Shape {
+        match self {
+            WorldShape::Sphere{ color, center, radius } => {
+                let radius = camera.project_size(radius, camera.depth(center));
+                Shape::Circle{ color,  center: camera.project(center),  radius }
+            }
+            WorldShape::Billboard{ tint, sprite, center, size } => {
+                let depth = camera.depth(center);
+                let size = size.map(|size| camera.project_size(size, depth));
+                let center = camera.project(center);
+                let area = [center[0]-size[0]/2.0, center[1]-size[1]/2.0, size[0], size[1]];
+                Shape::Image{ tint, sprite, area }
+            }
+            WorldShape::Line{ color, radius, ends } => {
+                let depth = (camera.depth(ends[0]) + camera.depth(ends[1])) / 2.0;
+                let [start, end] = [camera.project(ends[0]), camera.project(ends[1])];
+                Shape::Line {
+                    color,
+                    width: camera.project_size(radius, depth),
+                    area: [start[0], start[1], end[0], end[1]],
+                }
+            }
+            WorldShape::ShadedQuad{ colors, corners } => {
+                let points = corners.map(|corner| camera.project(corner));
+                let vertices = [0, 1, 2, 0, 2, 3].iter()
+                    .map(|&i| (points[i], colors[i]))
+                    .collect();
+                Shape::Triangles{ vertices }
+            }
+            WorldShape::Quad{ color, corners } => {
+                let points = corners.map(|corner| camera.project(corner));
+                let xs = points.map(|point| point[0]);
+                let ys = points.map(|point| point[1]);
+                let left = xs.iter().cloned().fold(f32::INFINITY, f32::min);
+                let right = xs.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+                let top = ys.iter().cloned().fold(f32::INFINITY, f32::min);
+                let bottom = ys.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+                let on_edge = |p: &[f32; 2]| (p[0] == left || p[0] == right) && (p[1] == top || p[1] == bottom);
+                if points.iter().all(on_edge) {
+                    // facing the camera
+                    Shape::Rectangle{ color,  area: [left, top, right-left, bottom-top] }
+                } else {
+                    Shape::Polygon{ color,  points: points.to_vec() }
+                }
+            }
+        }
+    }
+}
+
+/// Groups of shapes, which are drawn in this order
+/// no matter in which order shapes are added to them.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub enum Layer {
+    World,
+    Hud,
+    /// Menus and messages.
+    Overlay,
+    /// Hidden unless made visible with `Graphics.set_visible()`.
+    Debug,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 4] = [Layer::World, Layer::Hud, Layer::Overlay, Layer::Debug];
 }
 
 /// A list of `Shape`s to render.
 ///
 /// Games add elements to it in `Game.render()`,
 /// and engines consume it with `drain()`
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Graphics {
-    commands: Vec<Shape>,
+    /// Shapes for each layer, with the area they're clipped to.
+    layers: [Vec<(Option<[f32;4]>, Shape)>; 4],
+    visible: [bool; 4],
+    /// The layer shapes are added to.
+    layer: Layer,
+    /// Each clip is already limited to the ones before it.
+    clips: Vec<[f32;4]>,
+    /// Each transform already includes the ones before it.
+    transforms: Vec<Transform>,
+    text_metrics: Box<dyn MeasureText>,
+    world: Vec<WorldShape>,
+}
+
+impl Default for Graphics {
+    fn default() -> Self {
+        Graphics {
+            layers: Default::default(),
+            visible: Layer::ALL.map(|layer| layer != Layer::Debug),
+            layer: Layer::World,
+            clips: Vec::new(),
+            transforms: Vec::new(),
+            text_metrics: Box::new(FallbackMetrics),
+            world: Vec::new(),
+        }
+    }
 }
 
 impl Graphics {
     pub fn add(&mut self,  shape: Shape) {
-        self.commands.push(shape);
+        let clip = self.clips.last().cloned();
+        let shape = match self.transforms.last() {
+            Some(transform) => transform.apply_to_shape(shape),
+            None => shape,
+        };
+        self.layers[self.layer as usize].push((clip, shape));
+    }
+    /// Apply `transform` to everything added until `pop_transform()`,
+    /// before any transforms that were pushed before it.
+    pub fn push_transform(&mut self,  transform: Transform) {
+        let outer = self.transforms.last().cloned().unwrap_or_default();
+        self.transforms.push(transform.then(outer));
+    }
+    pub fn push_translate(&mut self,  offset: [f32; 2]) {
+        self.push_transform(Transform::translate(offset));
+    }
+    pub fn push_scale(&mut self,  factor: [f32; 2]) {
+        self.push_transform(Transform::scale(factor));
+    }
+    /// Rotate around `[0, 0]`, so usually combined with `push_translate()`.
+    pub fn push_rotate(&mut self,  angle: f32) {
+        self.push_transform(Transform::rotate(angle));
+    }
+    /// Go back to the transform before the last `push_transform()`
+    /// or one of its shorthands.
+    pub fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+    /// Used by engines to make `measure_text()` match how they draw text.
+    pub fn set_text_metrics(&mut self,  metrics: Box<dyn MeasureText>) {
+        self.text_metrics = metrics;
+    }
+    /// How large `text` will be when drawn with `size`,
+    /// in the current coordinate system.
+    ///
+    /// Without an engine this returns `FallbackMetrics`.
+    pub fn measure_text(&self,  text: &str,  size: f32) -> TextMetrics {
+        self.text_metrics.measure(text).scaled(size)
+    }
+    /// Add shapes to `layer` until it's changed again.
+    ///
+    /// Every frame starts out with `Layer::World`.
+    pub fn set_layer(&mut self,  layer: Layer) {
+        self.layer = layer;
+    }
+    /// Whether a layer is drawn.
+    /// This is remembered between frames.
+    pub fn set_visible(&mut self,  layer: Layer,  visible: bool) {
+        self.visible[layer as usize] = visible;
+    }
+    pub fn is_visible(&self,  layer: Layer) -> bool {
+        self.visible[layer as usize]
+    }
+    /// Don't draw anything outside `area` until `pop_clip()`.
+    ///
+    /// Clips are nested, so this can't draw outside the previous clip either.
+    /// A transformed `area` is replaced by the rectangle around it.
+    pub fn push_clip(&mut self,  area: [f32;4]) {
+        let area = match self.transforms.last() {
+            Some(transform) => transform.apply_to_area(area),
+            None => area,
+        };
+        let clip = match self.clips.last() {
+            Some(&[x, y, w, h]) => {
+                let left = f32::max(x, area[0]);
+                let top = f32::max(y, area[1]);
+                let right = f32::min(x+w, area[0]+area[2]);
+                let bottom = f32::min(y+h, area[1]+area[3]);
+                [left, top, (right-left).max(0.0), (bottom-top).max(0.0)]
+            }
+            None => area,
+        };
+        self.clips.push(clip);
+    }
+    /// Go back to the clip before the last `push_clip()`.
+    pub fn pop_clip(&mut self) {
+        self.clips.pop();
     }
     pub fn line(&mut self,  color: Color,  width: f32,  area: [f32;4]) {
-        self.commands.push(Shape::Line{color, width, area});
+        self.add(Shape::Line{color, width, area});
     }
     pub fn rectangle(&mut self,  color: Color,  area: [f32;4]) {
-        self.commands.push(Shape::Rectangle{ color, area });
+        self.add(Shape::Rectangle{ color, area });
     }
     pub fn circle(&mut self,  color: Color,  center: [f32; 2],  radius: f32) {
-        self.commands.push(Shape::Circle{ color, center, radius });
+        self.add(Shape::Circle{ color, center, radius });
+    }
+    pub fn polygon<P: Into<Vec<[f32; 2]>>>(&mut self,  color: Color,  points: P) {
+        self.add(Shape::Polygon{ color,  points: points.into() });
+    }
+    pub fn triangle_fan<P: Into<Vec<[f32; 2]>>>(&mut self,  color: Color,  points: P) {
+        self.add(Shape::TriangleFan{ color,  points: points.into() });
+    }
+    pub fn ellipse(&mut self,  color: Color,  area: [f32;4]) {
+        self.add(Shape::Ellipse{ color, area });
+    }
+    pub fn rounded_rectangle(&mut self,  color: Color,  area: [f32;4],  radius: f32) {
+        self.add(Shape::RoundedRectangle{ color, area, radius });
+    }
+    pub fn arc(&mut self,  color: Color,  width: f32,  center: [f32; 2],  radius: f32,  angles: [f32; 2]) {
+        self.add(Shape::Arc{ color, width, center, radius, angles });
+    }
+    pub fn stroke(&mut self,  color: Color,  width: f32,  outline: Outline) {
+        self.add(Shape::Stroke{ color, width, outline });
+    }
+    pub fn stroke_rectangle(&mut self,  color: Color,  width: f32,  area: [f32;4]) {
+        self.stroke(color, width, Outline::Rectangle{ area });
+    }
+    pub fn stroke_circle(&mut self,  color: Color,  width: f32,  center: [f32; 2],  radius: f32) {
+        let area = [center[0]-radius, center[1]-radius, radius*2.0, radius*2.0];
+        self.stroke(color, width, Outline::Ellipse{ area });
+    }
+    pub fn gradient<P: Into<Vec<[f32; 2]>>>(&mut self,  gradient: Gradient,  points: P) {
+        self.add(Shape::Gradient{ gradient,  points: points.into() });
+    }
+    pub fn triangles<V: Into<Vec<([f32; 2], Color)>>>(&mut self,  vertices: V) {
+        self.add(Shape::Triangles{ vertices: vertices.into() });
+    }
+    pub fn image(&mut self,  tint: Color,  sprite: Sprite,  area: [f32;4]) {
+        self.add(Shape::Image{ tint, sprite, area });
     }
     pub fn text<S: Into<Cow<'static, str>>>
-    (&mut self,  color: Color,  position: [f32; 2],  center: [Align; 2],  size: f32,  text: S) {
-        self.commands.push(match text.into() {
+    (&mut self,  color: Color,  position: [f32; 2],  center: (Align, VAlign),  size: f32,  text: S) {
+        self.add(match text.into() {
             Cow::Borrowed(s) => Shape::StaticText { color, size, position, center, text: s },
             Cow::Owned(s) => Shape::DynamicText { color, size, position, center, text: s },
         });
     }
-    /// Iterate over all elements and leave the list empty.
-    pub fn drain<'a>(&'a mut self) -> impl Iterator<Item=Shape> + 'a {
-        self.commands.drain(..)
+    pub fn text_box<S: Into<Cow<'static, str>>>
+    (&mut self,  color: Color,  position: [f32; 2],  layout: TextBox,  size: f32,  text: S) {
+        self.add(match text.into() {
+            Cow::Borrowed(s) => Shape::StaticTextBox { color, size, position, layout, text: s },
+            Cow::Owned(s) => Shape::DynamicTextBox { color, size, position, layout, text: s },
+        });
+    }
+    pub fn sphere(&mut self,  color: Color,  center: [f32; 3],  radius: f32) {
+        self.world.push(WorldShape::Sphere{ color, center, radius });
+    }
+    pub fn quad(&mut self,  color: Color,  corners: [[f32; 3]; 4]) {
+        self.world.push(WorldShape::Quad{ color, corners });
+    }
+    pub fn line_3d(&mut self,  color: Color,  radius: f32,  ends: [[f32; 3]; 2]) {
+        self.world.push(WorldShape::Line{ color, radius, ends });
+    }
+    pub fn shaded_quad(&mut self,  colors: [Color; 4],  corners: [[f32; 3]; 4]) {
+        self.world.push(WorldShape::ShadedQuad{ colors, corners });
+    }
+    pub fn billboard(&mut self,  tint: Color,  sprite: Sprite,  center: [f32; 3],  size: [f32; 2]) {
+        self.world.push(WorldShape::Billboard{ tint, sprite, center, size });
+    }
+    /// Project the `WorldShape`s added since the last call with `camera`,
+    /// and add them farthest first after the `Shape`s added so far.
+    ///
+    /// Shapes that are partially behind the camera are skipped.
+    pub fn draw_world(&mut self,  camera: &Camera) {
+        let mut world = std::mem::take(&mut self.world);
+        world.retain(|shape| shape.nearest(camera) > 0.0);
+        let mut sorted = world.into_iter()
+            .map(|shape| (shape.farthest(camera), shape))
+            .collect::<Vec<_>>();
+        // stable, so shapes at the same depth are drawn in the order they were added
+        sorted.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        for (_, shape) in sorted {
+            self.add(shape.project(camera));
+        }
+    }
+    /// Iterate over all elements of the visible layers in order,
+    /// with the area they are clipped to, and leave all layers empty.
+    pub fn drain<'a>(&'a mut self) -> impl Iterator<Item=(Option<[f32;4]>, Shape)> + 'a {
+        self.layer = Layer::World;
+        self.clips.clear();
+        self.transforms.clear();
+        self.layers.iter_mut()
+            .zip(self.visible)
+            .flat_map(|(shapes, visible)| shapes.drain(..).filter(move |_| visible))
     }
 }
 
-/// Parse a hex string of 6 or 8 bytes into a color.
-/// Format is rrggbbaa, where the aa is optional.
+/// Parse a color written in the code, like `hex("aaff55ee")`, in any format `Color::parse()` accepts.
+///
+/// Panics if it is invalid, so use `Color::parse()` for colors that come from files.
 #[track_caller]
 pub fn hex(color: &str) -> Color {
-    let a = match color.len() {
-        8 => u8::from_str_radix(&color[6..], 16).unwrap(),
-        6 => 255,
-        _ => panic!("color string must be 6 or 8 characters")
-    };
-    let r = u8::from_str_radix(&color[..2], 16).unwrap();
-    let g = u8::from_str_radix(&color[2..4], 16).unwrap();
-    let b = u8::from_str_radix(&color[4..6], 16).unwrap();
-    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0]
+    match Color::parse(color) {
+        Ok(color) => color,
+        Err(e) => panic!("{}", e),
+    }
 }
 
 /// Keys that the game cares about.
@@ -95,6 +682,27 @@ pub enum Key {
     Enter,
     Escape,
     Space,
+    Tab,
+    Backspace,
+    F3,
+    /// An uppercase ASCII letter.
+    Letter(char),
+}
+
+impl Key {
+    /// All keys that are not letters.
+    pub const NAMED: [Key; 10] = [
+        Key::ArrowUp,
+        Key::ArrowDown,
+        Key::ArrowLeft,
+        Key::ArrowRight,
+        Key::Enter,
+        Key::Escape,
+        Key::Space,
+        Key::Tab,
+        Key::Backspace,
+        Key::F3,
+    ];
 }
 
 /// All mouse buttons piston supports.
@@ -105,11 +713,45 @@ pub enum MouseButton {
     Middle,
 }
 
+/// The stages of a touch point's life.
+#[derive(Debug, Clone,Copy, PartialEq,Eq)]
+pub enum TouchPhase {
+    /// A finger was put down.
+    Start,
+    Move,
+    /// The finger was lifted.
+    End,
+    /// The touch was interrupted by something else, such as the browser.
+    Cancel,
+}
+
+/// Positions passed to and from the game are fractions of the largest square
+/// that fits in the center of the window.
+/// On windows that are not square, coordinates outside 0..1 are visible
+/// along the longer side.
 pub trait Game {
     fn render(&mut self,  gfx: &mut Graphics);
     fn update(&mut self,  dt: f32);
+    /// Called with the size of the window in pixels when it is created
+    /// and whenever it changes.
+    fn resize(&mut self,  size: [f32; 2]);
     fn key_press(&mut self,  key: Key);
     fn key_release(&mut self,  key: Key);
     fn mouse_move(&mut self,  pos: [f32; 2]);
     fn mouse_press(&mut self,  button: MouseButton);
+    fn mouse_release(&mut self,  button: MouseButton);
+    /// `id` identifies the finger for as long as it touches the screen,
+    /// and might be reused afterwards.
+    /// `pos` is in the same coordinate system as for `mouse_move()`.
+    fn touch(&mut self,  id: u64,  phase: TouchPhase,  pos: [f32; 2]);
+    /// Files to call `file_changed()` for when they are created or modified,
+    /// relative to the working directory.
+    /// A directory is changed when any file directly in it is.
+    ///
+    /// Only asked once, and engines that cannot watch files ignore it.
+    fn watched_files(&self) -> &'static [&'static str] {
+        &[]
+    }
+    /// `path` is one of those returned by `watched_files()`.
+    fn file_changed(&mut self,  _path: &'static str) {}
 }
@@ -0,0 +1,291 @@
+use crate::game::{Key, MouseButton};
+use crate::storage::{load_migrated, save_versioned, Category, Storage};
+
+use std::fmt::{self, Display, Write};
+use std::io;
+
+/// The version of the format `Bindings` are saved in.
+const BINDINGS_VERSION: u32 = 1;
+
+/// Things the player can do, independent of which key or button does it.
+#[derive(Debug, Clone,Copy, PartialEq,Eq)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    /// Start the round, or pause and un-pause when it's running.
+    Serve,
+    Pause,
+    Settings,
+    /// Show or hide `Layer::Debug`.
+    Debug,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Serve,
+        Action::Pause,
+        Action::Settings,
+        Action::Debug,
+    ];
+
+    /// The name used in binding files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveUp => "MoveUp",
+            Action::MoveDown => "MoveDown",
+            Action::MoveLeft => "MoveLeft",
+            Action::MoveRight => "MoveRight",
+            Action::Serve => "Serve",
+            Action::Pause => "Pause",
+            Action::Settings => "Settings",
+            Action::Debug => "Debug",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Action::ALL.iter().cloned().find(|action| action.name() == name)
+    }
+}
+
+/// Gamepad buttons, named after their position to not depend on the layout.
+///
+/// No engine reports these yet, but they can be bound.
+#[derive(Debug, Clone,Copy, PartialEq,Eq)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Start,
+    Select,
+}
+
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 10] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::West,
+        GamepadButton::North,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+        GamepadButton::Start,
+        GamepadButton::Select,
+    ];
+}
+
+/// A key or button that can be bound to an action.
+#[derive(Debug, Clone,Copy, PartialEq,Eq)]
+pub enum Input {
+    Key(Key),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl Display for Input {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Input::Key(Key::Letter(c)) => write!(fmtr, "key:{}", c),
+            Input::Key(key) => write!(fmtr, "key:{:?}", key),
+            Input::Mouse(button) => write!(fmtr, "mouse:{:?}", button),
+            Input::Gamepad(button) => write!(fmtr, "gamepad:{:?}", button),
+        }
+    }
+}
+
+impl Input {
+    /// Parse the format produced by `Display`, such as `key:ArrowUp` or `mouse:Left`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (device, name) = s.split_once(':')?;
+        let name = name.trim();
+        match device.trim() {
+            "key" => Key::NAMED.iter().cloned()
+                .find(|key| format!("{:?}", key) == name)
+                .or_else(|| {
+                    let mut chars = name.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c @ 'A'..='Z'), None) => Some(Key::Letter(c)),
+                        _ => None,
+                    }
+                })
+                .map(Input::Key),
+            "mouse" => [MouseButton::Left, MouseButton::Right, MouseButton::Middle].iter()
+                .cloned()
+                .find(|button| format!("{:?}", button) == name)
+                .map(Input::Mouse),
+            "gamepad" => GamepadButton::ALL.iter().cloned()
+                .find(|button| format!("{:?}", button) == name)
+                .map(Input::Gamepad),
+            _ => None,
+        }
+    }
+}
+
+/// Which inputs trigger which actions.
+///
+/// An input can only be bound to one action, but an action can have many inputs.
+/// The file format is one line per action, like `Serve = key:Space, mouse:Left`,
+/// with `#` starting comments.
+#[derive(Debug, Clone, PartialEq,Eq)]
+pub struct Bindings {
+    bindings: Vec<(Input, Action)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use self::Action::*;
+        let bindings = vec![
+            (Input::Key(Key::ArrowUp), MoveUp),
+            (Input::Key(Key::Letter('W')), MoveUp),
+            (Input::Gamepad(GamepadButton::DPadUp), MoveUp),
+            (Input::Key(Key::ArrowDown), MoveDown),
+            (Input::Key(Key::Letter('S')), MoveDown),
+            (Input::Gamepad(GamepadButton::DPadDown), MoveDown),
+            (Input::Key(Key::ArrowLeft), MoveLeft),
+            (Input::Key(Key::Letter('A')), MoveLeft),
+            (Input::Gamepad(GamepadButton::DPadLeft), MoveLeft),
+            (Input::Key(Key::ArrowRight), MoveRight),
+            (Input::Key(Key::Letter('D')), MoveRight),
+            (Input::Gamepad(GamepadButton::DPadRight), MoveRight),
+            // pausing with enter is a bit weird,
+            // but it's nice since it's close to the arrow keys. (and consistency)
+            (Input::Key(Key::Space), Serve),
+            (Input::Key(Key::Enter), Serve),
+            (Input::Mouse(MouseButton::Left), Serve),
+            (Input::Mouse(MouseButton::Right), Serve),
+            (Input::Mouse(MouseButton::Middle), Serve),
+            (Input::Gamepad(GamepadButton::South), Serve),
+            (Input::Key(Key::Escape), Pause),
+            (Input::Gamepad(GamepadButton::Start), Pause),
+            (Input::Key(Key::Tab), Settings),
+            (Input::Gamepad(GamepadButton::Select), Settings),
+            (Input::Key(Key::F3), Debug),
+        ];
+        Bindings { bindings }
+    }
+}
+
+impl Bindings {
+    /// Bindings where nothing is bound.
+    pub fn empty() -> Self {
+        Bindings { bindings: Vec::new() }
+    }
+
+    pub fn action(&self,  input: Input) -> Option<Action> {
+        self.bindings.iter()
+            .find(|&&(bound, _)| bound == input)
+            .map(|&(_, action)| action)
+    }
+
+    pub fn inputs(&self,  action: Action) -> impl Iterator<Item=Input> + '_ {
+        self.bindings.iter()
+            .filter(move |&&(_, bound)| bound == action)
+            .map(|&(input, _)| input)
+    }
+
+    /// Make `input` trigger `action`, removing any previous binding of `input`.
+    pub fn bind(&mut self,  input: Input,  action: Action) {
+        self.bindings.retain(|&(bound, _)| bound != input);
+        self.bindings.push((input, action));
+    }
+
+    /// Remove all inputs for `action`.
+    pub fn clear(&mut self,  action: Action) {
+        self.bindings.retain(|&(_, bound)| bound != action);
+    }
+
+    /// Parse the file format described on the type.
+    ///
+    /// Unknown actions or inputs are errors, to not silently lose bindings.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Bindings::parse_with_actions(s).map(|(bindings, _)| bindings)
+    }
+
+    /// Also returns which actions had a line, including those with no inputs.
+    fn parse_with_actions(s: &str) -> Result<(Self, Vec<Action>), String> {
+        let mut bindings = Bindings::empty();
+        let mut listed = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (action, inputs) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: missing =", i+1))?;
+            let action = Action::from_name(action.trim())
+                .ok_or_else(|| format!("line {}: unknown action {:?}", i+1, action.trim()))?;
+            listed.push(action);
+            for input in inputs.split(',').map(str::trim).filter(|input| !input.is_empty()) {
+                let input = Input::parse(input)
+                    .ok_or_else(|| format!("line {}: unknown input {:?}", i+1, input))?;
+                bindings.bind(input, action);
+            }
+        }
+        Ok((bindings, listed))
+    }
+
+    /// Give actions that were added after the bindings were saved their default inputs,
+    /// except inputs that have since been bound to something else.
+    fn add_new_actions(&mut self,  listed: &[Action]) {
+        for (input, action) in Bindings::default().bindings {
+            if !listed.contains(&action) && self.action(input).is_none() {
+                self.bindings.push((input, action));
+            }
+        }
+    }
+
+    /// Change bindings saved in `version` to the format of the next version.
+    fn migrate(version: u32,  contents: String) -> Result<String, String> {
+        match version {
+            // space_tennis_bindings.txt from before there was storage, in the same format
+            0 => Ok(contents),
+            _ => Err(format!("unknown version {}", version)),
+        }
+    }
+
+    /// Read bindings saved with `save()`, or None if none have been.
+    ///
+    /// Actions that are not in the saved bindings get their default inputs,
+    /// so that they can be used after upgrading.
+    pub fn load(storage: &dyn Storage) -> io::Result<Option<Self>> {
+        let contents = match load_migrated(storage, Category::Config, "bindings", BINDINGS_VERSION, Bindings::migrate)? {
+            Some(contents) => contents,
+            None => return Ok(None),
+        };
+        let (mut bindings, listed) = Bindings::parse_with_actions(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        bindings.add_new_actions(&listed);
+        Ok(Some(bindings))
+    }
+
+    /// Save the bindings, replacing any saved before.
+    pub fn save(&self,  storage: &mut dyn Storage) -> io::Result<()> {
+        save_versioned(storage, Category::Config, "bindings", BINDINGS_VERSION, &self.to_string())
+    }
+}
+
+impl Display for Bindings {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        for action in Action::ALL {
+            fmtr.write_str(action.name())?;
+            fmtr.write_str(" =")?;
+            for (i, input) in self.inputs(action).enumerate() {
+                fmtr.write_str(if i == 0 {" "} else {", "})?;
+                write!(fmtr, "{}", input)?;
+            }
+            fmtr.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,12 @@
 pub mod game;
+pub mod color;
+pub mod camera;
+pub mod transform;
+pub mod text;
+pub mod input;
+pub mod locale;
+pub mod palette;
+pub mod storage;
 #[cfg(feature="dyn")]
 pub mod reloading;
 
@@ -6,7 +14,7 @@ pub mod reloading;
 macro_rules! expose_game{($mod:tt::$game:tt) => {
     mod $mod;
 
-    pub use self::$mod::{NAME, INITIAL_SIZE};
+    pub use self::$mod::{NAME, INITIAL_SIZE, run_command_line};
     use self::$mod::$game;
 
     pub fn create_game() -> $game {
@@ -34,6 +42,13 @@ macro_rules! impl_main {($dir:tt) => {
             std::panic::set_hook(Box::new(console_error_panic_hook::hook));
             log::info!("{} WebGL", game::NAME);
         }
+        #[cfg(not(target_arch="wasm32"))]
+        {
+            let args = std::env::args().skip(1).collect::<Vec<_>>();
+            if let Some(code) = game::run_command_line(&args) {
+                std::process::exit(code);
+            }
+        }
 
         let game = game::create_game();
         #[cfg(feature="dyn")]
@@ -0,0 +1,331 @@
+//! Texts shown to the player, translated into different languages.
+//!
+//! Each locale has a catalog file with one message per line, like
+//! `controls.title = Controls`, where `#` at the start of a line makes it a comment.
+//! Messages can contain arguments like `{name}`, or `{name:.2}` for numbers with two decimals,
+//! and `{{` and `}}` for literal braces. `\n` is a line break.
+//! Messages that depend on a number have one line per plural form of the language,
+//! like `misses[one] = {count} miss` and `misses[other] = {count} misses`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The plural categories of the Unicode CLDR, of which each language uses a few.
+#[derive(Clone,Copy, Debug, PartialEq,Eq, Hash)]
+pub enum Plural {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    /// Used by all languages, and when a message lacks the form for a number.
+    Other,
+}
+
+impl Plural {
+    pub const ALL: [Plural; 6] = [
+        Plural::Zero,
+        Plural::One,
+        Plural::Two,
+        Plural::Few,
+        Plural::Many,
+        Plural::Other,
+    ];
+
+    /// The name used in catalog files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Plural::Zero => "zero",
+            Plural::One => "one",
+            Plural::Two => "two",
+            Plural::Few => "few",
+            Plural::Many => "many",
+            Plural::Other => "other",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Plural::ALL.iter().cloned().find(|plural| plural.name() == name)
+    }
+
+    /// Which form `count` takes in `language`, which is a code like `en`.
+    ///
+    /// Only a handful of languages have their own rules here,
+    /// the rest get the English one.
+    pub fn of(language: &str,  count: u64) -> Self {
+        let (ones, tens) = (count % 10, count % 100);
+        let few = (2..=4).contains(&ones) && !(12..=14).contains(&tens);
+        match language {
+            "ja" | "ko" | "zh" | "th" | "vi" => Plural::Other,
+            "fr" | "pt" if count <= 1 => Plural::One,
+            "fr" | "pt" => Plural::Other,
+            "pl" if count == 1 => Plural::One,
+            "pl" if few => Plural::Few,
+            "pl" => Plural::Many,
+            "ru" | "uk" if ones == 1 && tens != 11 => Plural::One,
+            "ru" | "uk" if few => Plural::Few,
+            "ru" | "uk" => Plural::Many,
+            _ if count == 1 => Plural::One,
+            _ => Plural::Other,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Message {
+    Text(Cow<'static, str>),
+    /// Always has `Plural::Other`.
+    Plural(Vec<(Plural, Cow<'static, str>)>),
+}
+
+impl Message {
+    fn forms(&self) -> impl Iterator<Item=&str> {
+        let (text, plurals) = match self {
+            Message::Text(text) => (Some(text.as_ref()), &[][..]),
+            Message::Plural(forms) => (None, &forms[..]),
+        };
+        text.into_iter().chain(plurals.iter().map(|(_, text)| text.as_ref()))
+    }
+}
+
+/// Replace arguments in `template` with their values, as described in the module documentation.
+///
+/// Unknown arguments are left as they are, to make them easy to spot.
+fn format_message(template: &str,  args: &[(&str, &dyn Display)]) -> String {
+    let mut formatted = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        formatted.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            formatted.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        let end = match rest.find('}') {
+            Some(end) if rest.starts_with('{') => end,
+            _ => {
+                formatted.push_str(&rest[..1]);
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let (name, precision) = match rest[1..end].split_once(":.") {
+            Some((name, precision)) => (name, precision.parse::<usize>().ok()),
+            None => (&rest[1..end], None),
+        };
+        match (args.iter().find(|&&(arg, _)| arg == name), precision) {
+            (Some((_, value)), Some(precision)) => write!(formatted, "{:.*}", precision, value).unwrap(),
+            (Some((_, value)), None) => write!(formatted, "{}", value).unwrap(),
+            (None, _) => formatted.push_str(&rest[..=end]),
+        }
+        rest = &rest[end+1..];
+    }
+    formatted.push_str(rest);
+    formatted
+}
+
+/// The names of the arguments used in `template`, in order of appearance.
+fn arguments(template: &str) -> impl Iterator<Item=&str> {
+    template.split('{')
+        .skip(1)
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.split_once('}'))
+        .map(|(argument, _)| argument.split(':').next().unwrap())
+}
+
+/// The messages of one locale.
+#[derive(Clone, Debug)]
+pub struct Catalog {
+    locale: String,
+    messages: HashMap<String, Message>,
+}
+
+impl Catalog {
+    /// Parse the file format described in the module documentation.
+    ///
+    /// Messages without escaped characters borrow from `source`.
+    pub fn parse(locale: &str,  source: &'static str) -> Result<Self, String> {
+        Catalog::parse_with(locale, source, Cow::Borrowed)
+    }
+
+    fn parse_with<'a>(locale: &str,  source: &'a str,  to_cow: fn(&'a str) -> Cow<'static, str>)
+    -> Result<Self, String> {
+        let mut messages = HashMap::new();
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, text) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: missing =", i+1))?;
+            let (key, text) = (key.trim(), text.trim());
+            let text = match text.contains('\\') {
+                false => to_cow(text),
+                true => Cow::Owned(text.replace("\\n", "\n").replace("\\\\", "\\")),
+            };
+            let (key, plural) = match key.strip_suffix(']').and_then(|key| key.split_once('[')) {
+                Some((key, plural)) => {
+                    let plural = Plural::from_name(plural)
+                        .ok_or_else(|| format!("line {}: unknown plural form {:?}", i+1, plural))?;
+                    (key, Some(plural))
+                }
+                None => (key, None),
+            };
+            match (messages.get_mut(key), plural) {
+                (None, None) => {
+                    messages.insert(key.to_string(), Message::Text(text));
+                }
+                (None, Some(plural)) => {
+                    messages.insert(key.to_string(), Message::Plural(vec![(plural, text)]));
+                }
+                (Some(Message::Plural(forms)), Some(plural))
+                if forms.iter().all(|&(form, _)| form != plural) => {
+                    forms.push((plural, text));
+                }
+                (Some(_), _) => return Err(format!("line {}: {} is defined twice", i+1, key)),
+            }
+        }
+        for (key, message) in &messages {
+            if let Message::Plural(forms) = message {
+                if forms.iter().all(|&(form, _)| form != Plural::Other) {
+                    return Err(format!("{} has no [other] form", key));
+                }
+            }
+        }
+        Ok(Catalog { locale: locale.to_string(),  messages })
+    }
+
+    /// Read a catalog from a file.
+    pub fn load(locale: &str,  path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Catalog::parse_with(locale, &contents, |text| Cow::Owned(text.to_string()))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// A code like `en` or `nb_NO`.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// The first part of the locale code, which decides the plural rules.
+    pub fn language(&self) -> &str {
+        self.locale.split(['_', '-']).next().unwrap()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item=&str> {
+        self.messages.keys().map(String::as_str)
+    }
+
+    /// The names of the arguments a message uses in any of its forms, sorted.
+    pub fn arguments(&self,  key: &str) -> Vec<&str> {
+        let mut used = self.messages.get(key).into_iter()
+            .flat_map(Message::forms)
+            .flat_map(arguments)
+            .collect::<Vec<_>>();
+        used.sort_unstable();
+        used.dedup();
+        used
+    }
+
+    fn template(&self,  key: &str,  count: Option<u64>) -> Option<&Cow<'static, str>> {
+        match (self.messages.get(key)?, count) {
+            (Message::Text(text), _) => Some(text),
+            (Message::Plural(forms), count) => {
+                let wanted = count.map_or(Plural::Other, |count| Plural::of(self.language(), count));
+                let form = |plural| forms.iter().find(|&&(form, _)| form == plural);
+                form(wanted).or_else(|| form(Plural::Other)).map(|(_, text)| text)
+            }
+        }
+    }
+}
+
+/// The catalogs for all available locales, and which of them to use.
+///
+/// Messages missing from the current locale are taken from the first catalog,
+/// and messages missing from that are shown as their key.
+#[derive(Clone, Debug)]
+pub struct Localization {
+    catalogs: Vec<Catalog>,
+    current: usize,
+}
+
+impl Localization {
+    pub fn new(default: Catalog) -> Self {
+        Localization { catalogs: vec![default],  current: 0 }
+    }
+
+    /// Make a locale available, replacing any catalog with the same locale.
+    pub fn add(&mut self,  catalog: Catalog) {
+        match self.catalogs.iter().position(|existing| existing.locale == catalog.locale) {
+            Some(i) => self.catalogs[i] = catalog,
+            None => self.catalogs.push(catalog),
+        }
+    }
+
+    pub fn catalogs(&self) -> &[Catalog] {
+        &self.catalogs
+    }
+
+    pub fn locale(&self) -> &str {
+        self.catalogs[self.current].locale()
+    }
+
+    /// Switch to the catalog for `locale`,
+    /// or one for the same language if there is none for the exact locale,
+    /// so that `nb_NO.UTF-8` selects `nb`.
+    ///
+    /// Returns whether there was a match.
+    pub fn set_locale(&mut self,  locale: &str) -> bool {
+        let locale = locale.split('.').next().unwrap();
+        let language = locale.split(['_', '-']).next().unwrap();
+        let found = self.catalogs.iter().position(|catalog| catalog.locale == locale)
+            .or_else(|| self.catalogs.iter().position(|catalog| catalog.language() == language));
+        if let Some(found) = found {
+            self.current = found;
+        }
+        found.is_some()
+    }
+
+    /// Switch to the locale after the current one, wrapping around.
+    pub fn next_locale(&mut self) {
+        self.current = (self.current + 1) % self.catalogs.len();
+    }
+
+    fn template(&self,  key: &str,  count: Option<u64>) -> Option<&Cow<'static, str>> {
+        self.catalogs[self.current].template(key, count)
+            .or_else(|| self.catalogs[0].template(key, count))
+    }
+
+    /// A message without arguments.
+    pub fn text(&self,  key: &str) -> Cow<'static, str> {
+        match self.template(key, None) {
+            Some(text) => text.clone(),
+            None => Cow::Owned(key.to_string()),
+        }
+    }
+
+    /// A message with its arguments replaced by `args`.
+    pub fn format(&self,  key: &str,  args: &[(&str, &dyn Display)]) -> String {
+        match self.template(key, None) {
+            Some(template) => format_message(template, args),
+            None => key.to_string(),
+        }
+    }
+
+    /// A message in the plural form for `count`, which is also available as the argument `count`.
+    pub fn plural(&self,  key: &str,  count: u64,  args: &[(&str, &dyn Display)]) -> String {
+        match self.template(key, Some(count)) {
+            Some(template) => {
+                let mut args = args.to_vec();
+                args.push(("count", &count));
+                format_message(template, &args)
+            }
+            None => key.to_string(),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Colors named after what they are used for, so that they can be changed together.
+//!
+//! A palette file has one color per line, like `ball = aaff55ee`,
+//! where `#` at the start of a line makes it a comment.
+//! Colors can be in any of the formats `Color::parse()` accepts.
+
+use crate::color::Color;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A set of named colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    name: String,
+    /// In the order they are in the file.
+    colors: Vec<(String, Color)>,
+}
+
+impl Palette {
+    /// Parse the file format described in the module documentation.
+    pub fn parse(name: &str,  source: &str) -> Result<Self, String> {
+        let mut colors = Vec::<(String, Color)>::new();
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (color_name, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: missing =", i+1))?;
+            let (color_name, value) = (color_name.trim(), value.trim());
+            if colors.iter().any(|(existing, _)| existing == color_name) {
+                return Err(format!("line {}: {} is defined twice", i+1, color_name));
+            }
+            let color = Color::parse(value).map_err(|e| format!("line {}: {}", i+1, e))?;
+            colors.push((color_name.to_string(), color));
+        }
+        Ok(Palette { name: name.to_string(),  colors })
+    }
+
+    /// Read a palette from a file.
+    pub fn load(name: &str,  path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Palette::parse(name, &contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the colors, in the order they are in the file.
+    pub fn names(&self) -> impl Iterator<Item=&str> {
+        self.colors.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn get(&self,  name: &str) -> Option<Color> {
+        self.colors.iter().find(|(existing, _)| existing == name).map(|&(_, color)| color)
+    }
+}
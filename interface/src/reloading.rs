@@ -8,10 +8,15 @@ use std::sync::atomic::{AtomicPtr, Ordering::*};
 pub struct Functions {
     pub render: unsafe fn(*mut c_void,  &mut Graphics),
     pub update: unsafe fn(*mut c_void,  f32),
+    pub resize: unsafe fn(*mut c_void,  [f32; 2]),
     pub key_press: unsafe fn(*mut c_void,  Key),
     pub key_release: unsafe fn(*mut c_void,  Key),
     pub mouse_move: unsafe fn(*mut c_void,  [f32; 2]),
     pub mouse_press: unsafe fn(*mut c_void,  MouseButton),
+    pub mouse_release: unsafe fn(*mut c_void,  MouseButton),
+    pub touch: unsafe fn(*mut c_void,  u64,  TouchPhase,  [f32; 2]),
+    pub watched_files: unsafe fn(*mut c_void) -> &'static [&'static str],
+    pub file_changed: unsafe fn(*mut c_void,  &'static str),
     pub size: usize
 }
 
@@ -44,6 +49,9 @@ impl Game for ReloadableGame {
     fn update(&mut self,  dt: f32) {
         unsafe{ (self.get().update)(self.game, dt) };
     }
+    fn resize(&mut self,  size: [f32; 2]) {
+        unsafe{ (self.get().resize)(self.game, size) };
+    }
     fn key_press(&mut self,  key: Key) {
         unsafe{ (self.get().key_press)(self.game, key) };
     }
@@ -53,22 +61,34 @@ impl Game for ReloadableGame {
     fn mouse_press(&mut self,  button: MouseButton) {
         unsafe{ (self.get().mouse_press)(self.game, button) };
     }
+    fn mouse_release(&mut self,  button: MouseButton) {
+        unsafe{ (self.get().mouse_release)(self.game, button) };
+    }
     fn mouse_move(&mut self,  pos: [f32; 2]) {
         unsafe{ (self.get().mouse_move)(self.game, pos) };
     }
+    fn touch(&mut self,  id: u64,  phase: TouchPhase,  pos: [f32; 2]) {
+        unsafe{ (self.get().touch)(self.game, id, phase, pos) };
+    }
+    fn watched_files(&self) -> &'static [&'static str] {
+        unsafe{ (self.get().watched_files)(self.game) }
+    }
+    fn file_changed(&mut self,  path: &'static str) {
+        unsafe{ (self.get().file_changed)(self.game, path) };
+    }
 }
 
 #[macro_export]
 macro_rules! expose_game_reloadably{($dir:literal/$mod:tt::$game:tt = $target:literal) => {
     mod $mod;
 
-    pub use self::$mod::{NAME, INITIAL_SIZE};
+    pub use self::$mod::{NAME, INITIAL_SIZE, run_command_line};
     use self::$mod::$game;
 
     use std::os::raw::c_void;
     use std::mem::size_of;
     use ::interface::reloading::{Functions, ReloadableGame};
-    use ::interface::game::{Game, Graphics, Key, MouseButton};
+    use ::interface::game::{Game, Graphics, Key, MouseButton, TouchPhase};
 
     unsafe fn game_render_dyn(gamestate: *mut c_void,  g: &mut Graphics) {
         (&mut*(gamestate as *mut $game)).render(g)
@@ -76,6 +96,9 @@ macro_rules! expose_game_reloadably{($dir:literal/$mod:tt::$game:tt = $target:li
     unsafe fn game_update_dyn(gamestate: *mut c_void,  deltatime: f32) {
         (&mut*(gamestate as *mut $game)).update(deltatime)
     }
+    unsafe fn game_resize_dyn(gamestate: *mut c_void,  size: [f32;2]) {
+        (&mut*(gamestate as *mut $game)).resize(size)
+    }
     unsafe fn game_key_press_dyn(gamestate: *mut c_void,  key: Key) {
         (&mut*(gamestate as *mut $game)).key_press(key)
     }
@@ -88,14 +111,31 @@ macro_rules! expose_game_reloadably{($dir:literal/$mod:tt::$game:tt = $target:li
     unsafe fn game_mouse_press_dyn(gamestate: *mut c_void,  button: MouseButton) {
         (&mut*(gamestate as *mut $game)).mouse_press(button)
     }
+    unsafe fn game_mouse_release_dyn(gamestate: *mut c_void,  button: MouseButton) {
+        (&mut*(gamestate as *mut $game)).mouse_release(button)
+    }
+    unsafe fn game_touch_dyn(gamestate: *mut c_void,  id: u64,  phase: TouchPhase,  pos: [f32;2]) {
+        (&mut*(gamestate as *mut $game)).touch(id, phase, pos)
+    }
+    unsafe fn game_watched_files_dyn(gamestate: *mut c_void) -> &'static [&'static str] {
+        (&*(gamestate as *mut $game)).watched_files()
+    }
+    unsafe fn game_file_changed_dyn(gamestate: *mut c_void,  path: &'static str) {
+        (&mut*(gamestate as *mut $game)).file_changed(path)
+    }
     #[no_mangle]
     pub static GAME: Functions = Functions {
         render: game_render_dyn,
         update: game_update_dyn,
+        resize: game_resize_dyn,
         key_press: game_key_press_dyn,
         key_release: game_key_release_dyn,
         mouse_move: game_mouse_move_dyn,
         mouse_press: game_mouse_press_dyn,
+        mouse_release: game_mouse_release_dyn,
+        touch: game_touch_dyn,
+        watched_files: game_watched_files_dyn,
+        file_changed: game_file_changed_dyn,
         size: size_of::<$game>()
     };
 
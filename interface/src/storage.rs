@@ -0,0 +1,245 @@
+//! Saving settings and other data between runs of the game.
+//!
+//! Natively things are saved as files in the XDG base directories,
+//! and in the browser in localStorage.
+//! Formats can change, so what is saved with `save_versioned()`
+//! gets a first line like `version = 2` that `load_versioned()` reads back,
+//! and `load_migrated()` brings older versions up to date one version at a time.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What is saved, which decides where it's saved.
+#[derive(Clone,Copy, Debug, PartialEq,Eq)]
+pub enum Category {
+    /// Settings and key bindings.
+    Config,
+    /// High scores and other things the game records.
+    Data,
+}
+
+/// Somewhere to save text by name.
+pub trait Storage {
+    /// What was saved as `name`, or None if nothing has been.
+    fn load(&self,  category: Category,  name: &str) -> io::Result<Option<String>>;
+    /// Replace what was saved as `name`.
+    fn save(&mut self,  category: Category,  name: &str,  contents: &str) -> io::Result<()>;
+}
+
+/// What was saved with `save_versioned()` along with its version,
+/// or version 0 if it was saved without one.
+pub fn load_versioned(storage: &dyn Storage,  category: Category,  name: &str)
+-> io::Result<Option<(u32, String)>> {
+    let contents = match storage.load(category, name)? {
+        Some(contents) => contents,
+        None => return Ok(None),
+    };
+    let first_line = contents.lines().next().unwrap_or("");
+    match first_line.split_once('=') {
+        Some((key, version)) if key.trim() == "version" => {
+            let version = version.trim().parse::<u32>().map_err(|_| io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{:?} is not a valid version", version.trim()),
+            ))?;
+            let rest = contents[first_line.len()..].trim_start_matches(['\r', '\n']);
+            Ok(Some((version, rest.to_string())))
+        }
+        _ => Ok(Some((0, contents))),
+    }
+}
+
+/// What was saved with `save_versioned()`, changed to the format of version `current`.
+///
+/// `migrate(version, contents)` is called for each version that is older than `current`,
+/// and should return `contents` changed to the format of the next version.
+/// Newer versions than `current` are errors, as they cannot be understood.
+pub fn load_migrated(storage: &dyn Storage,  category: Category,  name: &str,  current: u32,
+        migrate: fn(u32, String) -> Result<String, String>,
+) -> io::Result<Option<String>> {
+    let (mut version, mut contents) = match load_versioned(storage, category, name)? {
+        Some(loaded) => loaded,
+        None => return Ok(None),
+    };
+    if version > current {
+        return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("version {} is newer than this program", version),
+        ));
+    }
+    while version < current {
+        contents = migrate(version, contents).map_err(|e| io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("cannot upgrade from version {}: {}", version, e),
+        ))?;
+        version += 1;
+    }
+    Ok(Some(contents))
+}
+
+/// Save `contents` with a version line first.
+pub fn save_versioned(storage: &mut dyn Storage,  category: Category,  name: &str,  version: u32,  contents: &str)
+-> io::Result<()> {
+    storage.save(category, name, &format!("version = {}\n{}", version, contents))
+}
+
+/// Bring in a file that was used before there was storage, if nothing is saved as `name` yet.
+///
+/// The file is not changed, and what is saved has no version.
+/// Returns whether the file was imported.
+#[cfg(not(target_arch="wasm32"))]
+pub fn import_file(storage: &mut dyn Storage,  category: Category,  name: &str,  path: &Path)
+-> io::Result<bool> {
+    if storage.load(category, name)?.is_some() {
+        return Ok(false);
+    }
+    match fs::read_to_string(path) {
+        Ok(contents) => storage.save(category, name, &contents).map(|()| true),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Saves every name as a .txt file in a directory for each category.
+#[derive(Clone, Debug, PartialEq,Eq)]
+pub struct FileStorage {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+}
+
+impl FileStorage {
+    /// Use `$XDG_CONFIG_HOME/app` and `$XDG_DATA_HOME/app`,
+    /// or their defaults under `$HOME`.
+    /// Without `HOME` it's `config` and `data` in `%APPDATA%\app`,
+    /// or in `app` in the working directory without either.
+    pub fn new(app: &str) -> Self {
+        FileStorage::from_env(app, |var| env::var_os(var))
+    }
+
+    /// Like `new()`, but with the environment variables from `var`.
+    pub fn from_env(app: &str,  var: impl Fn(&str) -> Option<OsString>) -> Self {
+        let dir = |xdg_var: &str,  under_home: &str,  category: &str| {
+            match (var(xdg_var), var("HOME"), var("APPDATA")) {
+                (Some(dir), _, _) if !dir.is_empty() => Path::new(&dir).join(app),
+                (_, Some(home), _) if !home.is_empty() => Path::new(&home).join(under_home).join(app),
+                // the same directory is used for both, so they need subdirectories
+                (_, _, Some(appdata)) if !appdata.is_empty() => Path::new(&appdata).join(app).join(category),
+                _ => Path::new(app).join(category),
+            }
+        };
+        FileStorage {
+            config_dir: dir("XDG_CONFIG_HOME", ".config", "config"),
+            data_dir: dir("XDG_DATA_HOME", ".local/share", "data"),
+        }
+    }
+
+    /// Use `config` and `data` under `root`, such as a temporary directory.
+    pub fn in_dir(root: &Path) -> Self {
+        FileStorage { config_dir: root.join("config"),  data_dir: root.join("data") }
+    }
+
+    /// The file `name` is saved to.
+    pub fn path(&self,  category: Category,  name: &str) -> PathBuf {
+        let dir = match category {
+            Category::Config => &self.config_dir,
+            Category::Data => &self.data_dir,
+        };
+        dir.join(format!("{}.txt", name))
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self,  category: Category,  name: &str) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.path(category, name)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&mut self,  category: Category,  name: &str,  contents: &str) -> io::Result<()> {
+        let path = self.path(category, name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // write and rename, so that a crash cannot leave a half-written file
+        let temporary = path.with_extension("txt.new");
+        fs::write(&temporary, contents)?;
+        fs::rename(&temporary, &path)
+    }
+}
+
+/// Keeps everything in memory, for tests or when nothing should outlive the game.
+#[derive(Clone, Debug, Default, PartialEq,Eq)]
+pub struct MemoryStorage {
+    saved: Vec<(Category, String, String)>,
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self,  category: Category,  name: &str) -> io::Result<Option<String>> {
+        Ok(self.saved.iter()
+            .find(|(saved_category, saved_name, _)| *saved_category == category && saved_name == name)
+            .map(|(_, _, contents)| contents.clone()))
+    }
+
+    fn save(&mut self,  category: Category,  name: &str,  contents: &str) -> io::Result<()> {
+        self.saved.retain(|(saved_category, saved_name, _)| !(*saved_category == category && saved_name == name));
+        self.saved.push((category, name.to_string(), contents.to_string()));
+        Ok(())
+    }
+}
+
+/// Saves in the browser's localStorage, with keys like `app/config/name`.
+#[cfg(target_arch="wasm32")]
+#[derive(Clone, Debug, PartialEq,Eq)]
+pub struct LocalStorage {
+    app: String,
+}
+
+#[cfg(target_arch="wasm32")]
+impl LocalStorage {
+    pub fn new(app: &str) -> Self {
+        LocalStorage { app: app.to_string() }
+    }
+
+    fn key(&self,  category: Category,  name: &str) -> String {
+        let category = match category {
+            Category::Config => "config",
+            Category::Data => "data",
+        };
+        format!("{}/{}/{}", self.app, category, name)
+    }
+}
+
+#[cfg(target_arch="wasm32")]
+fn local_storage() -> io::Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "localStorage is not available"))
+}
+
+#[cfg(target_arch="wasm32")]
+fn js_error(error: wasm_bindgen::JsValue) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("{:?}", error))
+}
+
+#[cfg(target_arch="wasm32")]
+impl Storage for LocalStorage {
+    fn load(&self,  category: Category,  name: &str) -> io::Result<Option<String>> {
+        local_storage()?.get_item(&self.key(category, name)).map_err(js_error)
+    }
+
+    fn save(&mut self,  category: Category,  name: &str,  contents: &str) -> io::Result<()> {
+        local_storage()?.set_item(&self.key(category, name), contents).map_err(js_error)
+    }
+}
+
+/// The storage that fits the platform.
+pub fn open(app: &str) -> Box<dyn Storage> {
+    #[cfg(target_arch="wasm32")]
+    return Box::new(LocalStorage::new(app));
+    #[cfg(not(target_arch="wasm32"))]
+    return Box::new(FileStorage::new(app));
+}
@@ -0,0 +1,50 @@
+use std::fmt::{self, Debug};
+
+/// The size of a line of text, in the same unit as the size it's drawn with.
+#[derive(Clone,Copy, Debug, PartialEq)]
+pub struct TextMetrics {
+    pub width: f32,
+    pub height: f32,
+    /// The distance from the top to the line that letters stand on.
+    pub baseline: f32,
+}
+
+impl TextMetrics {
+    pub fn scaled(self,  factor: f32) -> Self {
+        TextMetrics {
+            width: self.width * factor,
+            height: self.height * factor,
+            baseline: self.baseline * factor,
+        }
+    }
+}
+
+/// Engines give `Graphics` one of these for the font they draw text with.
+pub trait MeasureText {
+    /// The metrics of `text` drawn with size 1.
+    fn measure(&self,  text: &str) -> TextMetrics;
+}
+
+impl Debug for dyn MeasureText {
+    fn fmt(&self,  fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.write_str("MeasureText")
+    }
+}
+
+/// Metrics that only depend on the number of characters,
+/// used when there is no engine, such as in tests,
+/// or when the engine can't draw text.
+///
+/// The proportions are close to those of the font the engines use.
+#[derive(Clone,Copy, Debug, Default)]
+pub struct FallbackMetrics;
+
+impl MeasureText for FallbackMetrics {
+    fn measure(&self,  text: &str) -> TextMetrics {
+        TextMetrics {
+            width: 0.34 * text.chars().count() as f32,
+            height: 1.0,
+            baseline: 0.75,
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::game::{arc_quads, Color, Outline, Shape};
+
+/// A combination of translation, scaling and rotation of screen coordinates.
+///
+/// `Graphics` applies the current transform to every shape added,
+/// so engines never see it.
+#[derive(Clone,Copy, Debug, PartialEq)]
+pub struct Transform {
+    /// Row-major 2x3 matrix, where the last column is the translation.
+    matrix: [[f32; 3]; 2],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform { matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] };
+
+    pub fn translate(offset: [f32; 2]) -> Self {
+        Transform { matrix: [[1.0, 0.0, offset[0]], [0.0, 1.0, offset[1]]] }
+    }
+
+    pub fn scale(factor: [f32; 2]) -> Self {
+        Transform { matrix: [[factor[0], 0.0, 0.0], [0.0, factor[1], 0.0]] }
+    }
+
+    /// Rotate around `[0, 0]` by `angle` radians clockwise, because Y points downwards.
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Transform { matrix: [[cos, -sin, 0.0], [sin, cos, 0.0]] }
+    }
+
+    /// A transform that does `self` first and then `next`.
+    pub fn then(self,  next: Transform) -> Self {
+        let [a, b] = next.matrix;
+        let [c, d] = self.matrix;
+        let row = |r: [f32; 3]| [
+            r[0]*c[0] + r[1]*d[0],
+            r[0]*c[1] + r[1]*d[1],
+            r[0]*c[2] + r[1]*d[2] + r[2],
+        ];
+        Transform { matrix: [row(a), row(b)] }
+    }
+
+    pub fn apply(&self,  [x, y]: [f32; 2]) -> [f32; 2] {
+        let [a, b] = self.matrix;
+        [a[0]*x + a[1]*y + a[2],  b[0]*x + b[1]*y + b[2]]
+    }
+
+    /// How much lengths grow on average, for things like line widths.
+    pub fn length_scale(&self) -> f32 {
+        let [a, b] = self.matrix;
+        (a[0]*b[1] - a[1]*b[0]).abs().sqrt()
+    }
+
+    /// Whether rectangles stay aligned to the X and Y axes.
+    fn keeps_axes(&self) -> bool {
+        self.matrix[0][1] == 0.0  &&  self.matrix[1][0] == 0.0
+    }
+
+    /// Whether circles stay circles, which they don't if stretched or mirrored.
+    fn keeps_circles(&self) -> bool {
+        let [a, b] = self.matrix;
+        (a[0]-b[1]).abs() <= f32::EPSILON*a[0].abs().max(1.0)
+        && (a[1]+b[0]).abs() <= f32::EPSILON*a[1].abs().max(1.0)
+    }
+
+    /// How much the X axis is rotated, in radians.
+    fn rotation(&self) -> f32 {
+        f32::atan2(self.matrix[1][0], self.matrix[0][0])
+    }
+
+    /// The smallest rectangle that contains `area` after transforming it.
+    pub fn apply_to_area(&self,  [x, y, w, h]: [f32; 4]) -> [f32; 4] {
+        let corners = [[x, y], [x+w, y], [x+w, y+h], [x, y+h]].map(|corner| self.apply(corner));
+        let left = corners.iter().map(|p| p[0]).fold(f32::INFINITY, f32::min);
+        let right = corners.iter().map(|p| p[0]).fold(f32::NEG_INFINITY, f32::max);
+        let top = corners.iter().map(|p| p[1]).fold(f32::INFINITY, f32::min);
+        let bottom = corners.iter().map(|p| p[1]).fold(f32::NEG_INFINITY, f32::max);
+        [left, top, right-left, bottom-top]
+    }
+
+    fn apply_to_points(&self,  points: Vec<[f32; 2]>) -> Vec<[f32; 2]> {
+        points.into_iter().map(|point| self.apply(point)).collect()
+    }
+
+    /// The corners of `area` in clockwise order from the top left, transformed.
+    fn corners(&self,  [x, y, w, h]: [f32; 4]) -> [[f32; 2]; 4] {
+        [[x, y], [x+w, y], [x+w, y+h], [x, y+h]].map(|corner| self.apply(corner))
+    }
+
+    fn quads_to_triangles(&self,  color: Color,  quads: Vec<[[f32; 2]; 4]>) -> Shape {
+        let vertices = quads.iter()
+            .flat_map(|quad| [0, 1, 2, 0, 2, 3].map(|i| (self.apply(quad[i]), color)))
+            .collect();
+        Shape::Triangles{ vertices }
+    }
+
+    /// Move a shape from the coordinate system this transform is for
+    /// to the one it transforms to.
+    ///
+    /// Shapes that engines can't rotate or stretch are turned into polygons,
+    /// except text which is only moved and resized.
+    pub fn apply_to_shape(&self,  shape: Shape) -> Shape {
+        match shape {
+            Shape::Line{ color, width, area } => {
+                let start = self.apply([area[0], area[1]]);
+                let end = self.apply([area[2], area[3]]);
+                let width = width * self.length_scale();
+                Shape::Line{ color,  width,  area: [start[0], start[1], end[0], end[1]] }
+            }
+            Shape::Rectangle{ color, area } if self.keeps_axes() => {
+                Shape::Rectangle{ color,  area: self.apply_to_area(area) }
+            }
+            Shape::Rectangle{ color, area } => {
+                Shape::Polygon{ color,  points: self.corners(area).to_vec() }
+            }
+            Shape::Circle{ color, center, radius } if self.keeps_circles() => {
+                Shape::Circle{ color,  center: self.apply(center),  radius: radius*self.length_scale() }
+            }
+            Shape::Circle{ color, center, radius } => {
+                let area = [center[0]-radius, center[1]-radius, radius*2.0, radius*2.0];
+                self.apply_to_shape(Shape::Ellipse{ color, area })
+            }
+            Shape::Polygon{ color, points } => {
+                Shape::Polygon{ color,  points: self.apply_to_points(points) }
+            }
+            Shape::TriangleFan{ color, points } => {
+                Shape::TriangleFan{ color,  points: self.apply_to_points(points) }
+            }
+            Shape::Ellipse{ color, area } if self.keeps_axes() => {
+                Shape::Ellipse{ color,  area: self.apply_to_area(area) }
+            }
+            Shape::Ellipse{ color, area } => {
+                let points = Outline::Ellipse{ area }.points(0.0);
+                Shape::Polygon{ color,  points: self.apply_to_points(points) }
+            }
+            Shape::RoundedRectangle{ color, area, radius } if self.keeps_axes() && self.keeps_circles() => {
+                let radius = radius * self.length_scale();
+                Shape::RoundedRectangle{ color,  area: self.apply_to_area(area),  radius }
+            }
+            Shape::RoundedRectangle{ color, area, radius } => {
+                let points = Outline::RoundedRectangle{ area, radius }.points(0.0);
+                Shape::Polygon{ color,  points: self.apply_to_points(points) }
+            }
+            Shape::Arc{ color, width, center, radius, angles } if self.keeps_circles() => {
+                let scale = self.length_scale();
+                let rotation = self.rotation();
+                Shape::Arc {
+                    color,
+                    width: width*scale,
+                    center: self.apply(center),
+                    radius: radius*scale,
+                    angles: [angles[0]+rotation, angles[1]+rotation],
+                }
+            }
+            Shape::Arc{ color, width, center, radius, angles } => {
+                self.quads_to_triangles(color, arc_quads(width, center, radius, angles))
+            }
+            Shape::Stroke{ color, width, outline } => {
+                self.quads_to_triangles(color, outline.stroke(width))
+            }
+            Shape::Image{ tint, sprite, area } if self.keeps_axes() => {
+                // mirroring is lost, but is unlikely to be wanted
+                Shape::Image{ tint,  sprite,  area: self.apply_to_area(area) }
+            }
+            Shape::Image{ tint, sprite, area } => {
+                Shape::ImageQuad{ tint,  sprite,  corners: self.corners(area) }
+            }
+            Shape::ImageQuad{ tint, sprite, corners } => {
+                Shape::ImageQuad{ tint,  sprite,  corners: corners.map(|corner| self.apply(corner)) }
+            }
+            Shape::Gradient{ gradient, points } => {
+                let vertices = gradient.triangles(&points).into_iter()
+                    .map(|(point, color)| (self.apply(point), color))
+                    .collect();
+                Shape::Triangles{ vertices }
+            }
+            Shape::Triangles{ vertices } => {
+                let vertices = vertices.into_iter()
+                    .map(|(point, color)| (self.apply(point), color))
+                    .collect();
+                Shape::Triangles{ vertices }
+            }
+            Shape::StaticText{ color, position, center, size, text } => {
+                let size = size * self.length_scale();
+                Shape::StaticText{ color,  position: self.apply(position),  center,  size,  text }
+            }
+            Shape::DynamicText{ color, position, center, size, text } => {
+                let size = size * self.length_scale();
+                Shape::DynamicText{ color,  position: self.apply(position),  center,  size,  text }
+            }
+            Shape::StaticTextBox{ color, position, mut layout, size, text } => {
+                let scale = self.length_scale();
+                layout.max_width *= scale;
+                Shape::StaticTextBox{ color,  position: self.apply(position),  layout,  size: size*scale,  text }
+            }
+            Shape::DynamicTextBox{ color, position, mut layout, size, text } => {
+                let scale = self.length_scale();
+                layout.max_width *= scale;
+                Shape::DynamicTextBox{ color,  position: self.apply(position),  layout,  size: size*scale,  text }
+            }
+        }
+    }
+}
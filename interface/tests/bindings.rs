@@ -0,0 +1,85 @@
+//! Checks the format bindings are saved in.
+
+extern crate interface;
+
+use interface::game::{Key, MouseButton};
+use interface::input::{Action, Bindings, GamepadButton, Input};
+use interface::storage::{Category, MemoryStorage, Storage};
+
+#[test]
+fn default_round_trip() {
+    let bindings = Bindings::default();
+    assert_eq!(Bindings::parse(&bindings.to_string()), Ok(bindings));
+}
+
+#[test]
+fn changed_round_trip() {
+    let mut bindings = Bindings::default();
+    bindings.clear(Action::Debug);
+    bindings.bind(Input::Key(Key::Letter('Q')), Action::Pause);
+    bindings.bind(Input::Mouse(MouseButton::Right), Action::Settings);
+    bindings.bind(Input::Gamepad(GamepadButton::North), Action::Debug);
+    bindings.bind(Input::Key(Key::Space), Action::MoveUp);
+    let saved = bindings.to_string();
+    let parsed = Bindings::parse(&saved).expect("parse saved bindings");
+    for action in Action::ALL {
+        assert_eq!(
+            parsed.inputs(action).collect::<Vec<_>>(),
+            bindings.inputs(action).collect::<Vec<_>>(),
+            "{} in\n{}", action.name(), saved
+        );
+    }
+    assert_eq!(parsed.to_string(), saved);
+}
+
+#[test]
+fn unbound_actions_are_kept() {
+    let mut bindings = Bindings::default();
+    bindings.clear(Action::Pause);
+    let saved = bindings.to_string();
+    assert!(saved.lines().any(|line| line == "Pause ="), "{}", saved);
+    let parsed = Bindings::parse(&saved).expect("parse saved bindings");
+    assert_eq!(parsed.inputs(Action::Pause).next(), None);
+}
+
+#[test]
+fn comments_and_errors() {
+    let parsed = Bindings::parse("# comment\nServe = key:Space, mouse:Left # and the mouse\n\n");
+    let parsed = parsed.expect("parse bindings with comments");
+    assert_eq!(parsed.action(Input::Key(Key::Space)), Some(Action::Serve));
+    assert_eq!(parsed.action(Input::Mouse(MouseButton::Left)), Some(Action::Serve));
+    assert!(Bindings::parse("Serve key:Space").unwrap_err().starts_with("line 1:"));
+    assert!(Bindings::parse("\nJump = key:Space").unwrap_err().starts_with("line 2:"));
+    assert!(Bindings::parse("Serve = key:Spacebar").unwrap_err().starts_with("line 1:"));
+}
+
+#[test]
+fn load_adds_new_actions() {
+    // saved before there was a debug action, and with F3 bound to pause
+    let mut storage = MemoryStorage::default();
+    let mut old = Bindings::default();
+    old.clear(Action::Debug);
+    old.bind(Input::Key(Key::F3), Action::Pause);
+    let old = old.to_string().lines()
+        .filter(|line| !line.starts_with("Debug"))
+        .map(|line| format!("{}\n", line))
+        .collect::<String>();
+    storage.save(Category::Config, "bindings", &old).unwrap();
+    let loaded = Bindings::load(&storage).unwrap().expect("bindings were saved");
+    assert_eq!(loaded.action(Input::Key(Key::F3)), Some(Action::Pause));
+
+    storage.save(Category::Config, "bindings", "Serve = key:Space\n").unwrap();
+    let loaded = Bindings::load(&storage).unwrap().expect("bindings were saved");
+    assert_eq!(loaded.action(Input::Key(Key::F3)), Some(Action::Debug));
+    assert_eq!(loaded.action(Input::Key(Key::Tab)), Some(Action::Settings));
+    assert_eq!(loaded.inputs(Action::Serve).collect::<Vec<_>>(), [Input::Key(Key::Space)]);
+}
+
+#[test]
+fn load_keeps_cleared_actions() {
+    let mut storage = MemoryStorage::default();
+    let mut bindings = Bindings::default();
+    bindings.clear(Action::Debug);
+    bindings.save(&mut storage).unwrap();
+    assert_eq!(Bindings::load(&storage).unwrap(), Some(bindings));
+}
@@ -0,0 +1,40 @@
+//! Checks the color formats that palette files can use.
+
+extern crate interface;
+
+use interface::color::Color;
+
+fn parse(color: &str) -> String {
+    Color::parse(color).unwrap_or_else(|e| panic!("{}", e)).to_string()
+}
+
+#[test]
+fn formats() {
+    assert_eq!(parse("#abc"), "#aabbccff");
+    assert_eq!(parse("#abcd"), "#aabbccdd");
+    assert_eq!(parse("#AABBCC"), "#aabbccff");
+    assert_eq!(parse("aabbcc80"), "#aabbcc80");
+    assert_eq!(parse("CornflowerBlue"), "#6495edff");
+    assert_eq!(parse("transparent"), "#00000000");
+    assert_eq!(parse("hsl(120, 100%, 50%)"), "#00ff00ff");
+    assert_eq!(parse("hsl(0deg 100% 50% / 0.5)"), "#ff000080");
+    assert_eq!(parse("hsla(240, 100%, 25%, 50%)"), "#00008080");
+}
+
+#[test]
+fn invalid() {
+    for color in ["", "abc", "#ab", "#abcde", "reed", "hsl(1, 2, 3)", "hsl(1, 2%, 3%", "hsl(1, 2%)"] {
+        assert!(Color::parse(color).is_err(), "{:?} was accepted", color);
+    }
+}
+
+#[test]
+fn helpers() {
+    let (white, black) = (Color::WHITE, Color::BLACK);
+    assert!((white.contrast_ratio(black) - 21.0).abs() < 0.01);
+    assert_eq!(black.contrast_ratio(black), 1.0);
+    assert_eq!(white.lerp(black, 0.5).to_string(), "#808080ff");
+    assert_eq!(Color::parse("red").unwrap().lighten(0.5).to_string(), "#ff8080ff");
+    assert_eq!(Color::parse("#ff000080").unwrap().premultiply().to_string(), "#80000080");
+    assert_eq!(Color::from([0.5, 0.25, 1.0, 1.0]).to_array(), [0.5, 0.25, 1.0, 1.0]);
+}
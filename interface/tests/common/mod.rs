@@ -0,0 +1,36 @@
+//! Loading and comparing the data files in the repository.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Load every `.txt` file in a directory at the top of the repository,
+/// sorted by file name and with the file name without extension.
+pub fn load_dir<T>(dir: &str,  load: fn(&str, &Path) -> io::Result<T>) -> Vec<(String, T)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join(dir);
+    let mut loaded = fs::read_dir(&dir).unwrap_or_else(|e| panic!("{}: {}", dir.display(), e))
+        .map(|entry| entry.expect("read directory").path())
+        .filter(|path| path.extension() == Some("txt".as_ref()))
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_str().expect("UTF-8 file name").to_string();
+            let value = load(&name, &path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            (name, value)
+        })
+        .collect::<Vec<_>>();
+    loaded.sort_by(|a, b| a.0.cmp(&b.0));
+    loaded
+}
+
+/// Describe which of the `keys` of file `from` that `has` is false for in file `name`.
+pub fn lacks<'a>(name: &str,  has: impl Fn(&str) -> bool,  from: &str,  keys: impl Iterator<Item=&'a str>)
+-> Vec<String> {
+    keys.filter(|&key| !has(key))
+        .map(|key| format!("{} lacks {} from {}", name, key, from))
+        .collect()
+}
+
+/// Fail with every problem if there are any.
+pub fn assert_none(mut problems: Vec<String>) {
+    problems.sort();
+    assert!(problems.is_empty(), "{}", problems.join("\n"));
+}
@@ -0,0 +1,34 @@
+//! Checks gradients that have no length.
+
+extern crate interface;
+
+use interface::color::Color;
+use interface::game::Gradient;
+
+const COLORS: [Color; 2] = [Color::BLACK, Color::WHITE];
+const SQUARE: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+#[test]
+fn normal_gradients() {
+    let linear = Gradient::Linear { from: [0.0, 0.0],  to: [1.0, 0.0],  colors: COLORS };
+    assert_eq!(linear.color_at([-1.0, 0.0]), Color::BLACK);
+    assert_eq!(linear.color_at([0.5, 3.0]), Color::BLACK.lerp(Color::WHITE, 0.5));
+    assert_eq!(linear.color_at([2.0, 0.0]), Color::WHITE);
+    let radial = Gradient::Radial { center: [0.0, 0.0],  radius: 2.0,  colors: COLORS };
+    assert_eq!(radial.color_at([0.0, 0.0]), Color::BLACK);
+    assert_eq!(radial.color_at([0.0, 1.0]), Color::BLACK.lerp(Color::WHITE, 0.5));
+}
+
+#[test]
+fn zero_length() {
+    let linear = Gradient::Linear { from: [0.5, 0.5],  to: [0.5, 0.5],  colors: COLORS };
+    let radial = Gradient::Radial { center: [0.5, 0.5],  radius: 0.0,  colors: COLORS };
+    for gradient in [linear, radial] {
+        for point in [[0.5, 0.5], [0.0, 1.0]] {
+            assert_eq!(gradient.color_at(point), Color::WHITE, "{:?} at {:?}", gradient, point);
+        }
+        let triangles = gradient.triangles(&SQUARE);
+        assert!(!triangles.is_empty(), "{:?}", gradient);
+        assert!(triangles.iter().all(|&(_, color)| color == Color::WHITE), "{:?}", gradient);
+    }
+}
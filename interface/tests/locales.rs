@@ -0,0 +1,45 @@
+//! Checks that the catalogs in locale/ are complete.
+
+extern crate interface;
+
+mod common;
+
+use common::{assert_none, lacks, load_dir};
+use interface::locale::Catalog;
+
+fn catalogs() -> Vec<Catalog> {
+    load_dir("locale", Catalog::load).into_iter().map(|(_, catalog)| catalog).collect()
+}
+
+#[test]
+fn every_key_in_every_locale() {
+    let catalogs = catalogs();
+    assert!(catalogs.iter().any(|catalog| catalog.locale() == "en"), "no English catalog");
+    let mut missing = Vec::new();
+    for catalog in &catalogs {
+        for other in &catalogs {
+            let has = |key: &str| catalog.keys().any(|existing| existing == key);
+            missing.extend(lacks(catalog.locale(), has, other.locale(), other.keys()));
+        }
+    }
+    assert_none(missing);
+}
+
+#[test]
+fn same_arguments_in_every_locale() {
+    let catalogs = catalogs();
+    let mut different = Vec::new();
+    for catalog in &catalogs {
+        for other in &catalogs {
+            for key in catalog.keys() {
+                let (arguments, others) = (catalog.arguments(key), other.arguments(key));
+                if catalog.locale() < other.locale() && arguments != others {
+                    different.push(format!("{}: {} has {:?} but {} has {:?}",
+                        key, catalog.locale(), arguments, other.locale(), others
+                    ));
+                }
+            }
+        }
+    }
+    assert_none(different);
+}
@@ -0,0 +1,129 @@
+//! Checks saving to files, in a temporary directory.
+
+extern crate interface;
+
+use interface::input::{Action, Bindings, Input};
+use interface::game::Key;
+use interface::storage::*;
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// A directory that is removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(test: &str) -> Self {
+        let dir = env::temp_dir().join(format!("space_tennis_{}_{}", test, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        TempDir(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn save_and_load() {
+    let dir = TempDir::new("save_and_load");
+    let mut storage = FileStorage::in_dir(&dir.0);
+    assert_eq!(storage.load(Category::Config, "settings").unwrap(), None);
+    storage.save(Category::Config, "settings", "theme = dark\n").unwrap();
+    storage.save(Category::Data, "settings", "something else").unwrap();
+    assert_eq!(storage.load(Category::Config, "settings").unwrap().as_deref(), Some("theme = dark\n"));
+    assert_eq!(storage.load(Category::Data, "settings").unwrap().as_deref(), Some("something else"));
+    assert!(storage.path(Category::Data, "settings").starts_with(dir.0.join("data")));
+    storage.save(Category::Config, "settings", "theme = light\n").unwrap();
+    assert_eq!(storage.load(Category::Config, "settings").unwrap().as_deref(), Some("theme = light\n"));
+}
+
+#[test]
+fn versions() {
+    let dir = TempDir::new("versions");
+    let mut storage = FileStorage::in_dir(&dir.0);
+    save_versioned(&mut storage, Category::Config, "settings", 3, "theme = dark\n").unwrap();
+    let loaded = load_versioned(&storage, Category::Config, "settings").unwrap();
+    assert_eq!(loaded, Some((3, "theme = dark\n".to_string())));
+    storage.save(Category::Config, "settings", "theme = dark\n").unwrap();
+    let loaded = load_versioned(&storage, Category::Config, "settings").unwrap();
+    assert_eq!(loaded, Some((0, "theme = dark\n".to_string())));
+    storage.save(Category::Config, "settings", "version = x\n").unwrap();
+    assert!(load_versioned(&storage, Category::Config, "settings").is_err());
+}
+
+#[test]
+fn import_old_bindings() {
+    let dir = TempDir::new("import_old_bindings");
+    let mut storage = FileStorage::in_dir(&dir.0);
+    let old = dir.0.join("space_tennis_bindings.txt");
+    fs::create_dir_all(&dir.0).unwrap();
+    fs::write(&old, "Serve = key:Space\n").unwrap();
+    assert!(import_file(&mut storage, Category::Config, "bindings", &old).unwrap());
+    assert!(!import_file(&mut storage, Category::Config, "bindings", &old).unwrap());
+    let mut bindings = Bindings::load(&storage).unwrap().expect("imported");
+    assert_eq!(bindings.action(Input::Key(Key::Space)), Some(Action::Serve));
+    bindings.bind(Input::Key(Key::Enter), Action::Serve);
+    bindings.save(&mut storage).unwrap();
+    let saved = storage.load(Category::Config, "bindings").unwrap().unwrap();
+    assert!(saved.starts_with("version = "));
+    let loaded = Bindings::load(&storage).unwrap().unwrap();
+    assert_eq!(loaded.action(Input::Key(Key::Enter)), Some(Action::Serve));
+}
+
+/// Adds a line for each version it's upgraded past.
+fn migrate(version: u32,  contents: String) -> Result<String, String> {
+    match version {
+        0 | 1 => Ok(format!("{}upgraded from {}\n", contents, version)),
+        _ => Err("cannot".to_string()),
+    }
+}
+
+#[test]
+fn migrations() {
+    let dir = TempDir::new("migrations");
+    let mut storage = FileStorage::in_dir(&dir.0);
+    assert_eq!(load_migrated(&storage, Category::Config, "settings", 2, migrate).unwrap(), None);
+    storage.save(Category::Config, "settings", "old\n").unwrap();
+    let loaded = load_migrated(&storage, Category::Config, "settings", 2, migrate).unwrap();
+    assert_eq!(loaded.as_deref(), Some("old\nupgraded from 0\nupgraded from 1\n"));
+    save_versioned(&mut storage, Category::Config, "settings", 1, "newer\n").unwrap();
+    let loaded = load_migrated(&storage, Category::Config, "settings", 2, migrate).unwrap();
+    assert_eq!(loaded.as_deref(), Some("newer\nupgraded from 1\n"));
+    save_versioned(&mut storage, Category::Config, "settings", 2, "current\n").unwrap();
+    let loaded = load_migrated(&storage, Category::Config, "settings", 2, migrate).unwrap();
+    assert_eq!(loaded.as_deref(), Some("current\n"));
+    let error = load_migrated(&storage, Category::Config, "settings", 1, migrate).unwrap_err();
+    assert_eq!(error.to_string(), "version 2 is newer than this program");
+    let error = load_migrated(&storage, Category::Config, "settings", 3, migrate).unwrap_err();
+    assert_eq!(error.to_string(), "cannot upgrade from version 2: cannot");
+}
+
+/// The directories `FileStorage::from_env()` picks with only the given environment variables.
+fn dirs(vars: &[(&str, &str)]) -> (PathBuf, PathBuf) {
+    let storage = FileStorage::from_env("game", |name| {
+        vars.iter().find(|&&(var, _)| var == name).map(|&(_, value)| OsString::from(value))
+    });
+    let dir = |category| storage.path(category, "file").parent().unwrap().to_path_buf();
+    (dir(Category::Config), dir(Category::Data))
+}
+
+#[test]
+fn directories() {
+    let home = Path::new("home");
+    let both = [("XDG_CONFIG_HOME", "config"), ("XDG_DATA_HOME", "data"), ("HOME", "home"), ("APPDATA", "appdata")];
+    assert_eq!(dirs(&both), (Path::new("config").join("game"), Path::new("data").join("game")));
+    let home_only = [("HOME", "home"), ("APPDATA", "appdata")];
+    assert_eq!(dirs(&home_only), (home.join(".config").join("game"), home.join(".local/share").join("game")));
+    let empty = [("XDG_CONFIG_HOME", ""), ("HOME", "home")];
+    assert_eq!(dirs(&empty).0, home.join(".config").join("game"));
+    let appdata = Path::new("appdata").join("game");
+    assert_eq!(dirs(&[("APPDATA", "appdata")]), (appdata.join("config"), appdata.join("data")));
+    let app = Path::new("game");
+    assert_eq!(dirs(&[]), (app.join("config"), app.join("data")));
+}
@@ -0,0 +1,22 @@
+//! Checks that the palettes in themes/ have the same colors.
+
+extern crate interface;
+
+mod common;
+
+use common::{assert_none, lacks, load_dir};
+use interface::palette::Palette;
+
+#[test]
+fn same_colors_in_every_theme() {
+    let palettes = load_dir("themes", Palette::load);
+    let (_, dark) = palettes.iter().find(|(name, _)| name == "dark").expect("no dark theme");
+    let mut different = Vec::new();
+    for (_, palette) in &palettes {
+        let has = |color: &str| palette.get(color).is_some();
+        different.extend(lacks(palette.name(), has, dark.name(), dark.names()));
+        let has = |color: &str| dark.get(color).is_some();
+        different.extend(lacks(dark.name(), has, palette.name(), palette.names()));
+    }
+    assert_none(different);
+}